@@ -0,0 +1,232 @@
+//! Internals shared by `docxide-template` and its proc macros, so text is
+//! located and placeholders are matched the same way at compile time and when
+//! rendering. Do not depend on this crate directly.
+
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+/// WordprocessingML main namespaces, transitional and strict.
+const WORDPROCESSING_NAMESPACES: [&[u8]; 2] = [
+    b"http://schemas.openxmlformats.org/wordprocessingml/2006/main",
+    b"http://purl.oclc.org/ooxml/wordprocessingml/main",
+];
+
+pub fn is_wordprocessing(ns: &ResolveResult) -> bool {
+    match ns {
+        ResolveResult::Bound(Namespace(uri)) => WORDPROCESSING_NAMESPACES.contains(uri),
+        // Fragments without namespace declarations use the conventional prefix
+        ResolveResult::Unknown(prefix) => prefix == b"w",
+        ResolveResult::Unbound => false,
+    }
+}
+
+/// WordprocessingML elements whose text may hold placeholders: run text and
+/// field instructions. Each is matched as a separate stream of text.
+pub const TEXT_ELEMENTS: [&[u8]; 2] = [b"t", b"instrText"];
+
+/// The text content of one WordprocessingML element in a part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSpan {
+    /// Byte offsets of the content in the part, between the start and end tags.
+    pub start: usize,
+    pub end: usize,
+    /// The content with entities decoded.
+    pub text: String,
+    /// The content couldn't be decoded (e.g. an unknown entity), so `text` is
+    /// the content as written and must not be escaped again.
+    pub raw: bool,
+}
+
+/// Finds the content of every text element of a part, one stream of spans per
+/// element in [`TEXT_ELEMENTS`], whatever prefix the part binds the
+/// WordprocessingML namespace to.
+pub fn locate_text(xml: &str) -> Vec<Vec<TextSpan>> {
+    let mut reader = NsReader::from_str(xml);
+    reader.config_mut().check_end_names = false;
+    let mut streams: Vec<Vec<TextSpan>> = TEXT_ELEMENTS.iter().map(|_| Vec::new()).collect();
+    let mut current: Option<(usize, TextSpan)> = None;
+    loop {
+        let before = reader.buffer_position() as usize;
+        match reader.read_resolved_event() {
+            Ok((ns, Event::Start(e))) if is_wordprocessing(&ns) => {
+                current = TEXT_ELEMENTS.iter().position(|name| e.local_name().as_ref() == *name).map(|kind| {
+                    let start = reader.buffer_position() as usize;
+                    (kind, TextSpan { start, end: start, text: String::new(), raw: false })
+                });
+            }
+            Ok((_, Event::Text(t))) => {
+                if let Some((_, span)) = current.as_mut() {
+                    match t.unescape() {
+                        Ok(text) => span.text.push_str(&text),
+                        Err(_) => {
+                            span.text.push_str(&String::from_utf8_lossy(&t));
+                            span.raw = true;
+                        }
+                    }
+                }
+            }
+            Ok((_, Event::CData(c))) => {
+                if let Some((_, span)) = current.as_mut() {
+                    span.text.push_str(&String::from_utf8_lossy(&c));
+                }
+            }
+            Ok((_, Event::End(e))) => {
+                if let Some((kind, mut span)) = current.take() {
+                    if e.local_name().as_ref() == TEXT_ELEMENTS[kind] {
+                        span.end = before;
+                        streams[kind].push(span);
+                    }
+                }
+            }
+            // Any other element inside means this isn't a plain text element
+            Ok((_, Event::Start(_) | Event::Empty(_))) => current = None,
+            Ok((_, Event::Eof)) | Err(_) => break,
+            _ => {}
+        }
+    }
+    streams
+}
+
+/// Finds placeholder occurrences in `text` as `(start, end, value)`.
+///
+/// Matches are taken left to right and never overlap, preferring the longest
+/// pattern at a position, so an escape like `\{` wins over a placeholder
+/// starting at the brace it escapes.
+pub fn find_matches<V: Copy>(text: &str, replacements: &[(&str, V)]) -> Vec<(usize, usize, V)> {
+    // Only positions starting with the first byte of some pattern can match
+    let mut first_bytes = [false; 256];
+    for (placeholder, _) in replacements {
+        if let Some(&b) = placeholder.as_bytes().first() {
+            first_bytes[b as usize] = true;
+        }
+    }
+
+    let bytes = text.as_bytes();
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        if !first_bytes[bytes[start] as usize] {
+            start += 1;
+            continue;
+        }
+        let rest = &bytes[start..];
+        let longest = replacements
+            .iter()
+            .filter(|(placeholder, _)| !placeholder.is_empty() && rest.starts_with(placeholder.as_bytes()))
+            .max_by_key(|(placeholder, _)| placeholder.len());
+        match longest {
+            Some(&(placeholder, value)) => {
+                matches.push((start, start + placeholder.len(), value));
+                start += placeholder.len();
+            }
+            None => start += 1,
+        }
+    }
+    matches
+}
+
+/// Edits to one span of a stream: its index and `(from, to, value)` over its
+/// decoded text, where `None` empties the range.
+pub type SpanEdits<V> = (usize, Vec<(usize, usize, Option<V>)>);
+
+/// Matches placeholders against the joined text of `spans`, so a placeholder
+/// split across runs is still found. Its value goes into the first run and the
+/// rest are emptied. Only spans with edits are returned.
+pub fn span_edits<V: Copy>(spans: &[TextSpan], replacements: &[(&str, V)]) -> Vec<SpanEdits<V>> {
+    let concatenated: String = spans.iter().map(|span| span.text.as_str()).collect();
+    let mut span_starts = Vec::with_capacity(spans.len());
+    let mut offset = 0;
+    for span in spans {
+        span_starts.push(offset);
+        offset += span.text.len();
+    }
+    // The span holding byte `pos` of the joined text, and the offset within it
+    let locate = |pos: usize| {
+        let span_idx = span_starts.partition_point(|&start| start <= pos) - 1;
+        (span_idx, pos - span_starts[span_idx])
+    };
+
+    let mut edits: Vec<Vec<(usize, usize, Option<V>)>> = vec![Vec::new(); spans.len()];
+    for (match_start, match_end, value) in find_matches(&concatenated, replacements) {
+        let (start_span, start_off) = locate(match_start);
+        let (end_span, end_off) = locate(match_end - 1);
+        if start_span == end_span {
+            edits[start_span].push((start_off, end_off + 1, Some(value)));
+        } else {
+            edits[start_span].push((start_off, spans[start_span].text.len(), Some(value)));
+            for (mid, span) in spans.iter().enumerate().take(end_span).skip(start_span + 1) {
+                edits[mid].push((0, span.text.len(), None));
+            }
+            edits[end_span].push((0, end_off + 1, None));
+        }
+    }
+
+    edits.into_iter().enumerate().filter(|(_, edits)| !edits.is_empty()).collect()
+}
+
+/// Decodes the percent-encoded braces (`%7B`, `%7D`) Word writes for braces
+/// typed into a hyperlink address, along with the offset in `value` of each
+/// byte of the decoded string and of its end.
+pub fn decode_percent_braces(value: &str) -> (String, Vec<usize>) {
+    let mut decoded = String::with_capacity(value.len());
+    let mut offsets = Vec::with_capacity(value.len() + 1);
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        let offset = value.len() - rest.len();
+        let brace = match rest.get(..3) {
+            Some("%7B" | "%7b") => Some('{'),
+            Some("%7D" | "%7d") => Some('}'),
+            _ => None,
+        };
+        match brace {
+            Some(brace) => {
+                decoded.push(brace);
+                offsets.push(offset);
+                rest = &rest[3..];
+            }
+            None => {
+                decoded.push(c);
+                offsets.extend((0..c.len_utf8()).map(|i| offset + i));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    offsets.push(value.len());
+    (decoded, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_text_and_instructions_in_streams() {
+        let xml = r#"<w:p><w:r><w:tab/><w:t>{Na</w:t></w:r><w:r><w:instrText>{x}</w:instrText><w:t>me} &amp;</w:t></w:r></w:p>"#;
+        let streams = locate_text(xml);
+        let found: Vec<Vec<_>> = streams
+            .iter()
+            .map(|spans| spans.iter().map(|s| (&xml[s.start..s.end], s.text.as_str())).collect())
+            .collect();
+        assert_eq!(found, vec![vec![("{Na", "{Na"), ("me} &amp;", "me} &")], vec![("{x}", "{x}")]]);
+    }
+
+    #[test]
+    fn edits_across_spans() {
+        let spans: Vec<TextSpan> = ["Hi {Na", "m", "e}!"]
+            .iter()
+            .map(|text| TextSpan { start: 0, end: 0, text: text.to_string(), raw: false })
+            .collect();
+        assert_eq!(
+            span_edits(&spans, &[("{Name}", 7)]),
+            vec![(0, vec![(3, 6, Some(7))]), (1, vec![(0, 1, None)]), (2, vec![(0, 2, None)])]
+        );
+    }
+
+    #[test]
+    fn decodes_percent_braces_with_offsets() {
+        let (decoded, offsets) = decode_percent_braces("a%7bx%7D%20");
+        assert_eq!(decoded, "a{x}%20");
+        assert_eq!(offsets, vec![0, 1, 4, 5, 8, 9, 10, 11]);
+    }
+}
@@ -0,0 +1,260 @@
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Attribute, Ident, LitStr, Path, Token, Visibility};
+
+use std::collections::HashMap;
+
+use crate::naming::{Collisions, FieldNaming};
+use crate::placeholders::{Delimiters, FieldOrder};
+
+/// Arguments to `generate_templates!`: the template directory followed by
+/// optional `key = value` settings.
+pub(crate) struct TemplateArgs {
+    pub(crate) path: LitStr,
+    pub(crate) delimiters: Delimiters,
+    /// Derives added to every generated struct and dropdown enum, after
+    /// `Debug` and `Clone`.
+    pub(crate) derives: Vec<Path>,
+    /// Outer attributes put on every generated struct and dropdown enum.
+    pub(crate) attributes: Vec<Attribute>,
+    /// Visibility of the generated types, their fields and methods, `pub` by default.
+    pub(crate) visibility: Visibility,
+    pub(crate) order: FieldOrder,
+    /// Set with the `rename`, `keyword_suffix` and `collisions` options.
+    pub(crate) naming: FieldNaming,
+    /// Renames for one template only, by the name of the type it generates.
+    pub(crate) template_renames: HashMap<String, HashMap<String, String>>,
+}
+
+impl TemplateArgs {
+    /// The naming of the template generating `type_name`: its own renames
+    /// over those for every template.
+    pub(crate) fn naming_for(&self, type_name: &str) -> FieldNaming {
+        let mut naming = self.naming.clone();
+        if let Some(renames) = self.template_renames.get(type_name) {
+            naming.renames.extend(renames.clone());
+        }
+        naming
+    }
+}
+
+fn parse_delimiters(input: ParseStream) -> syn::Result<Delimiters> {
+    let content;
+    parenthesized!(content in input);
+    let open: LitStr = content.parse()?;
+    content.parse::<Token![,]>()?;
+    let close: LitStr = content.parse()?;
+    content.parse::<Option<Token![,]>>()?;
+    if open.value().trim().is_empty() || close.value().trim().is_empty() {
+        return Err(syn::Error::new(open.span(), "delimiters must not be empty or whitespace"));
+    }
+    Ok(Delimiters::new(open.value(), close.value()))
+}
+
+fn parse_derives(input: ParseStream) -> syn::Result<Vec<Path>> {
+    let content;
+    parenthesized!(content in input);
+    let derives = Punctuated::<Path, Token![,]>::parse_terminated(&content)?;
+    // Always derived already
+    Ok(derives
+        .into_iter()
+        .filter(|path| !path.is_ident("Debug") && !path.is_ident("Clone"))
+        .collect())
+}
+
+fn parse_attributes(input: ParseStream) -> syn::Result<Vec<Attribute>> {
+    let content;
+    parenthesized!(content in input);
+    let mut attributes = Vec::new();
+    while !content.is_empty() {
+        attributes.extend(content.call(Attribute::parse_outer)?);
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(attributes)
+}
+
+/// Renames for every template, and for one template only by its type name.
+type Renames = (HashMap<String, String>, HashMap<String, HashMap<String, String>>);
+
+/// `("ZIPCODE" => zip_code, Invoice = ("Total" => amount), ...)`, naming the
+/// field each placeholder fills, in every template or in the one generating
+/// `Invoice`. Placeholders renamed to the same field are merged into it.
+fn parse_renames(input: ParseStream) -> syn::Result<Renames> {
+    let content;
+    parenthesized!(content in input);
+    let mut renames = HashMap::new();
+    let mut template_renames: HashMap<String, HashMap<String, String>> = HashMap::new();
+    while !content.is_empty() {
+        if content.peek(Ident) {
+            let template: Ident = content.parse()?;
+            content.parse::<Token![=]>()?;
+            let inner;
+            parenthesized!(inner in content);
+            if template_renames.insert(template.to_string(), parse_rename_pairs(&inner)?).is_some() {
+                return Err(syn::Error::new(template.span(), "template renames given more than once"));
+            }
+        } else {
+            parse_rename_pair(&content, &mut renames)?;
+        }
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok((renames, template_renames))
+}
+
+fn parse_rename_pairs(input: ParseStream) -> syn::Result<HashMap<String, String>> {
+    let mut renames = HashMap::new();
+    while !input.is_empty() {
+        parse_rename_pair(input, &mut renames)?;
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+    }
+    Ok(renames)
+}
+
+fn parse_rename_pair(input: ParseStream, renames: &mut HashMap<String, String>) -> syn::Result<()> {
+    let placeholder: LitStr = input.parse()?;
+    input.parse::<Token![=>]>()?;
+    let field: Ident = input.parse()?;
+    if renames.insert(placeholder.value(), field.to_string()).is_some() {
+        return Err(syn::Error::new(placeholder.span(), "placeholder renamed more than once"));
+    }
+    Ok(())
+}
+
+fn parse_keyword_suffix(input: ParseStream) -> syn::Result<String> {
+    let suffix: LitStr = input.parse()?;
+    if suffix.value().is_empty() || syn::parse_str::<Ident>(&format!("type{}", suffix.value())).is_err() {
+        return Err(syn::Error::new(suffix.span(), "keyword_suffix must be letters, digits or underscores"));
+    }
+    Ok(suffix.value())
+}
+
+fn parse_collisions(input: ParseStream) -> syn::Result<Collisions> {
+    let policy: Ident = input.parse()?;
+    match policy.to_string().as_str() {
+        "merge" => Ok(Collisions::Merge),
+        "error" => Ok(Collisions::Error),
+        "suffix" => Ok(Collisions::Suffix),
+        other => Err(syn::Error::new(
+            policy.span(),
+            format!("unknown collisions policy `{}`, expected `merge`, `error` or `suffix`", other),
+        )),
+    }
+}
+
+fn parse_order(input: ParseStream) -> syn::Result<FieldOrder> {
+    let order: Ident = input.parse()?;
+    match order.to_string().as_str() {
+        "document" => Ok(FieldOrder::Document),
+        "alphabetical" => Ok(FieldOrder::Alphabetical),
+        "declared" => Ok(FieldOrder::Declared),
+        other => Err(syn::Error::new(
+            order.span(),
+            format!("unknown order `{}`, expected `document`, `alphabetical` or `declared`", other),
+        )),
+    }
+}
+
+impl Parse for TemplateArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: LitStr = input.parse().map_err(|e| {
+            syn::Error::new(
+                e.span(),
+                "expected a string literal, e.g. generate_templates!(\"path/to/templates\")",
+            )
+        })?;
+        let mut args = TemplateArgs {
+            path,
+            delimiters: Delimiters::default(),
+            derives: Vec::new(),
+            attributes: Vec::new(),
+            visibility: syn::parse_quote!(pub),
+            order: FieldOrder::Document,
+            naming: FieldNaming::default(),
+            template_renames: HashMap::new(),
+        };
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "delimiters" => args.delimiters = parse_delimiters(input)?,
+                "derive" => args.derives = parse_derives(input)?,
+                "attributes" => args.attributes = parse_attributes(input)?,
+                "visibility" => args.visibility = input.parse()?,
+                "order" => args.order = parse_order(input)?,
+                "rename" => (args.naming.renames, args.template_renames) = parse_renames(input)?,
+                "keyword_suffix" => args.naming.keyword_suffix = Some(parse_keyword_suffix(input)?),
+                "collisions" => args.naming.collisions = parse_collisions(input)?,
+                other => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown option `{}`, expected `delimiters`, `derive`, `attributes`, `visibility`, `order`, `rename`, `keyword_suffix` or `collisions`",
+                            other
+                        ),
+                    ))
+                }
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Settings from `#[docx(...)]` on a struct deriving `DocxTemplate`.
+pub(crate) struct DeriveArgs {
+    pub(crate) path: LitStr,
+    pub(crate) delimiters: Delimiters,
+}
+
+impl DeriveArgs {
+    pub(crate) fn from_attributes(ident: &Ident, attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut path = None;
+        let mut delimiters = Delimiters::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("docx")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("path") {
+                    path = Some(meta.value()?.parse::<LitStr>()?);
+                } else if meta.path.is_ident("delimiters") {
+                    delimiters = parse_delimiters(meta.value()?)?;
+                } else {
+                    return Err(meta.error("unknown option, expected `path` or `delimiters`"));
+                }
+                Ok(())
+            })?;
+        }
+        let path = path.ok_or_else(|| {
+            syn::Error::new(
+                ident.span(),
+                "missing template path, e.g. #[docx(path = \"templates/Invoice.docx\")]",
+            )
+        })?;
+        Ok(Self { path, delimiters })
+    }
+}
+
+/// The placeholder name given to a field with `#[docx(rename = "...")]`.
+pub(crate) fn field_rename(attrs: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("docx")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                rename = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown option, expected `rename`"))
+            }
+        })?;
+    }
+    Ok(rename)
+}
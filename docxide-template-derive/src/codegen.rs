@@ -1,64 +1,398 @@
-use quote::quote;
+use heck::ToPascalCase;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use crate::args::TemplateArgs;
+use crate::placeholders::{FieldType, StructContent};
+use crate::precompute::Precomputed;
+
+/// Serde derives for generated types, with the `serde` feature.
+fn serde_derives(serde: bool) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if !serde {
+        return (quote! {}, quote! {});
+    }
+    (
+        quote! { , docxide_template::__private::serde::Serialize, docxide_template::__private::serde::Deserialize },
+        quote! { #[serde(crate = "docxide_template::__private::serde")] },
+    )
+}
+
+/// Why the user's `derive` can't be implemented for a struct with these
+/// fields, naming the first field in the way.
+fn derive_conflict(derive: &syn::Path, fields: &[syn::Ident], field_types: &[FieldType], serde: bool) -> Option<String> {
+    let name = derive.segments.last()?.ident.to_string();
+    fields.iter().zip(field_types).find_map(|(field, field_type)| {
+        let reason = match (name.as_str(), field_type) {
+            ("Copy", FieldType::Text) => "is a `String`",
+            ("Copy", FieldType::Choice { open: true, .. }) => "is a combo box enum, which may hold a `String`",
+            ("Default", FieldType::Choice { .. }) => "is a dropdown enum, which has no default value",
+            ("Default", FieldType::Date { .. }) => "is a `Date`, which has no default value",
+            ("Serialize" | "Deserialize", FieldType::Date { .. }) if !serde => {
+                "is a `Date`, which implements it only with docxide-template's `serde` feature"
+            }
+            ("Copy" | "Eq" | "Hash" | "Ord", FieldType::ChartSeries) => "is a `Vec<(String, f64)>`",
+            _ => return None,
+        };
+        Some(format!("`{}` field {}", field.unraw(), reason))
+    })
+}
+
+fn generate_choice_enum(
+    enum_ident: &syn::Ident,
+    field_type: &FieldType,
+    vis: &syn::Visibility,
+    serde: bool,
+    user_derives: &[&syn::Path],
+    attributes: &[syn::Attribute],
+) -> proc_macro2::TokenStream {
+    let FieldType::Choice { variants, open } = field_type else {
+        return quote! {};
+    };
+    // Leave out what the enum derives already
+    let user_derives = user_derives.iter().filter(|path| {
+        let name = path.segments.last().map(|s| s.ident.to_string());
+        match name.as_deref() {
+            Some("PartialEq" | "Eq" | "Hash") => false,
+            Some("Copy") => *open,
+            _ => true,
+        }
+    });
+    let idents: Vec<_> = variants.iter().map(|v| &v.ident).collect();
+    let display_texts: Vec<_> = variants.iter().map(|v| v.display_text.as_str()).collect();
+    // Variants (de)serialize as the text shown in the list
+    let (derives, serde_attr) = serde_derives(serde);
+    let renames: Vec<_> = if serde {
+        display_texts.iter().map(|text| quote! { #[serde(rename = #text)] }).collect()
+    } else {
+        display_texts.iter().map(|_| quote! {}).collect()
+    };
+    let untagged = if serde { quote! { #[serde(untagged)] } } else { quote! {} };
+
+    if *open {
+        quote! {
+            #[derive(Debug, Clone, PartialEq, Eq, Hash #derives #(, #user_derives)*)]
+            #serde_attr
+            #(#attributes)*
+            #vis enum #enum_ident {
+                #(#renames #idents,)*
+                /// Free text, allowed because the template uses a combo box.
+                #untagged
+                Other(String),
+            }
+
+            impl #enum_ident {
+                #vis fn as_str(&self) -> &str {
+                    match self {
+                        #(Self::#idents => #display_texts,)*
+                        Self::Other(text) => text.as_str(),
+                    }
+                }
+            }
+
+            impl std::fmt::Display for #enum_ident {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        }
+    } else {
+        quote! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash #derives #(, #user_derives)*)]
+            #serde_attr
+            #(#attributes)*
+            #vis enum #enum_ident {
+                #(#renames #idents,)*
+            }
+
+            impl #enum_ident {
+                #vis fn as_str(&self) -> &'static str {
+                    match self {
+                        #(Self::#idents => #display_texts,)*
+                    }
+                }
+            }
+
+            impl docxide_template::__private::Sealed for #enum_ident {}
+
+            impl docxide_template::DropdownValue for #enum_ident {
+                const ENTRIES: &'static [&'static str] = &[#(#display_texts),*];
+            }
+
+            impl std::fmt::Display for #enum_ident {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        }
+    }
+}
+
+/// The enum generated for the dropdown or combo box field `field` of `type_ident`.
+fn choice_enum_ident(type_ident: &syn::Ident, field: &syn::Ident) -> syn::Ident {
+    format_ident!("{}{}", type_ident, field.unraw().to_string().to_pascal_case())
+}
+
+/// Types generated for a template besides its struct, which must not clash
+/// with the types of other templates.
+pub(crate) fn companion_type_names(type_ident: &syn::Ident, content: &StructContent) -> Vec<String> {
+    let builder = (!content.fields.is_empty()).then(|| builder_ident(type_ident).to_string());
+    content
+        .fields
+        .iter()
+        .zip(&content.field_types)
+        .filter(|(_, field_type)| matches!(field_type, FieldType::Choice { .. }))
+        .map(|(field, _)| choice_enum_ident(type_ident, field).to_string())
+        .chain(builder)
+        .collect()
+}
+
+fn builder_ident(type_ident: &syn::Ident) -> syn::Ident {
+    format_ident!("{}Builder", type_ident)
+}
+
+/// The builder setter for each field, named after it. `build` finishes the
+/// builder, so a field named `build` is set with `set_build` instead, or
+/// `set_set_build` if that is a field too, and so on.
+pub(crate) fn builder_setters(fields: &[syn::Ident]) -> Vec<syn::Ident> {
+    fields
+        .iter()
+        .map(|field| {
+            if field.unraw() != "build" {
+                return field.clone();
+            }
+            let mut setter = format_ident!("set_build");
+            while fields.contains(&setter) {
+                setter = format_ident!("set_{}", setter);
+            }
+            setter
+        })
+        .collect()
+}
 
 pub(crate) fn generate_struct(
     type_ident: syn::Ident,
     abs_path: &str,
-    fields: &[syn::Ident],
-    replacement_placeholders: &[syn::LitStr],
-    replacement_fields: &[syn::Ident],
+    content: &StructContent,
+    args: &TemplateArgs,
     embed: bool,
+    serde: bool,
+    precomputed: &Precomputed,
 ) -> proc_macro2::TokenStream {
+    let StructContent {
+        fields,
+        field_types,
+        aliases,
+        replacement_placeholders,
+        replacement_fields,
+        escapes,
+        chart_placeholders,
+        chart_fields,
+        collisions: _,
+        renamed: _,
+    } = content;
     let has_fields = !fields.is_empty();
+    let TemplateArgs { derives, attributes, visibility: vis, .. } = args;
+    // The serde feature derives these already
+    let derives: Vec<_> = derives
+        .iter()
+        .filter(|path| {
+            let name = path.segments.last().map(|s| s.ident.to_string());
+            !(serde && matches!(name.as_deref(), Some("Serialize" | "Deserialize")))
+        })
+        .collect();
+    // Derives the field types can't support fail with one error naming the
+    // field, rather than with errors from the derive's expansion
+    let (derives, conflicts): (Vec<_>, Vec<_>) =
+        derives.into_iter().partition(|path| derive_conflict(path, fields, field_types, serde).is_none());
+    let derive_errors = conflicts.iter().map(|path| {
+        let message = format!(
+            "`{}` can't derive `{}`: its {}",
+            type_ident,
+            quote!(#path).to_string().replace(' ', ""),
+            derive_conflict(path, fields, field_types, serde).unwrap_or_default()
+        );
+        quote! { compile_error!(#message); }
+    });
     let abs_path_lit = syn::LitStr::new(abs_path, proc_macro::Span::call_site().into());
 
-    let save_and_bytes = if embed {
+    let mut enums = Vec::new();
+    let mut field_tys = Vec::new();
+    let mut param_tys = Vec::new();
+    let mut inits = Vec::new();
+    for (field, field_type) in fields.iter().zip(field_types) {
+        match field_type {
+            FieldType::Text => {
+                field_tys.push(quote! { String });
+                param_tys.push(quote! { impl Into<String> });
+                inits.push(quote! { #field.into() });
+            }
+            FieldType::Choice { .. } => {
+                let enum_ident = choice_enum_ident(&type_ident, field);
+                enums.push(generate_choice_enum(&enum_ident, field_type, vis, serde, &derives, attributes));
+                field_tys.push(quote! { #enum_ident });
+                param_tys.push(quote! { #enum_ident });
+                inits.push(quote! { #field });
+            }
+            FieldType::Date { .. } => {
+                field_tys.push(quote! { docxide_template::Date });
+                param_tys.push(quote! { docxide_template::Date });
+                inits.push(quote! { #field });
+            }
+            FieldType::ChartSeries => {
+                field_tys.push(quote! { Vec<(String, f64)> });
+                param_tys.push(quote! { Vec<(String, f64)> });
+                inits.push(quote! { #field });
+            }
+        }
+    }
+
+    let replacement_values = replacement_fields.iter().map(|field| {
+        let field_type = fields
+            .iter()
+            .position(|f| f == field)
+            .map(|i| &field_types[i])
+            .unwrap_or(&FieldType::Text);
+        match field_type {
+            FieldType::Date { format } => {
+                quote! { std::borrow::Cow::Owned(self.#field.format(#format)) }
+            }
+            _ => quote! { std::borrow::Cow::Borrowed(self.#field.as_str()) },
+        }
+    });
+
+    let escape_sequences = escapes.iter().map(|(escape, _)| escape);
+    let escape_literals = escapes.iter().map(|(_, literal)| literal);
+    let escape_replacements = quote! {
+        #( (#escape_sequences, std::borrow::Cow::Borrowed(#escape_literals)), )*
+    };
+
+    let chart_series = if chart_fields.is_empty() {
+        quote! {}
+    } else {
         quote! {
-            const TEMPLATE_BYTES: &'static [u8] = include_bytes!(#abs_path_lit);
+            fn chart_series(&self) -> Vec<(&str, &[(String, f64)])> {
+                vec![
+                    #( (#chart_placeholders, self.#chart_fields.as_slice()), )*
+                ]
+            }
+        }
+    };
 
-            pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), docxide_template::TemplateError> {
-                use docxide_template::DocxTemplate;
-                docxide_template::__private::save_docx_bytes(
-                    Self::TEMPLATE_BYTES,
-                    path.as_ref().with_extension("docx").as_path(),
-                    &self.replacements(),
-                )
+    let save_and_bytes = render_methods(vis, &abs_path_lit, embed, precomputed);
+
+    let (serde_derives, serde_attr) = serde_derives(serde);
+    let field_attrs = aliases.iter().map(|aliases| {
+        if serde {
+            quote! { #(#[serde(alias = #aliases)])* }
+        } else {
+            quote! {}
+        }
+    });
+    let from_json = if serde && !has_fields {
+        quote! {
+            /// Checks that `json` is valid JSON. The template has no placeholders,
+            /// so nothing is taken from it.
+            #vis fn from_json(json: &str) -> Result<Self, docxide_template::TemplateError> {
+                docxide_template::__private::from_json::<docxide_template::__private::serde::de::IgnoredAny>(json)
+                    .map(|_| Self)
             }
 
-            pub fn to_bytes(&self) -> Result<Vec<u8>, docxide_template::TemplateError> {
-                use docxide_template::DocxTemplate;
-                docxide_template::__private::build_docx_bytes(Self::TEMPLATE_BYTES, &self.replacements())
+            /// Checks that `data` serializes to a struct or map, like
+            /// [`from_json`](Self::from_json) for templates with placeholders.
+            #vis fn from_data<D: docxide_template::__private::serde::Serialize + ?Sized>(
+                data: &D,
+            ) -> Result<Self, docxide_template::TemplateError> {
+                docxide_template::__private::from_data::<docxide_template::__private::serde::de::IgnoredAny, D>(data)
+                    .map(|_| Self)
             }
         }
-    } else {
+    } else if serde {
         quote! {
-            pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), docxide_template::TemplateError> {
-                docxide_template::__private::save_docx(self, path.as_ref().with_extension("docx"))
+            /// Deserializes the field values from JSON, keyed by field name or
+            /// by the placeholder names as written in the template.
+            #vis fn from_json(json: &str) -> Result<Self, docxide_template::TemplateError> {
+                docxide_template::__private::from_json(json)
             }
 
-            pub fn to_bytes(&self) -> Result<Vec<u8>, docxide_template::TemplateError> {
-                use docxide_template::DocxTemplate;
-                let template_bytes = std::fs::read(self.template_path())?;
-                docxide_template::__private::build_docx_bytes(&template_bytes, &self.replacements())
+            /// Takes the field values from any serializable struct or map, keyed
+            /// like [`from_json`](Self::from_json). Nested fields fill dotted
+            /// placeholders, so `{customer.name}` is taken from `customer.name`.
+            #vis fn from_data<D: docxide_template::__private::serde::Serialize + ?Sized>(
+                data: &D,
+            ) -> Result<Self, docxide_template::TemplateError> {
+                docxide_template::__private::from_data(data)
             }
         }
+    } else {
+        quote! {}
     };
 
+    let builder_ident = builder_ident(&type_ident);
+    let setters = builder_setters(fields);
+    let field_names: Vec<_> = fields.iter().map(|field| field.unraw().to_string()).collect();
+    let builder_doc = format!("Builds a [`{}`] one field at a time.", type_ident);
+    let setter_docs = field_names.iter().map(|name| format!("Sets `{}`.", name));
+
     if has_fields {
         quote! {
-            #[derive(Debug, Clone)]
-            pub struct #type_ident {
-                #(pub #fields: String,)*
+            #(#derive_errors)*
+
+            #(#enums)*
+
+            #[doc = #builder_doc]
+            #[derive(Debug, Clone, Default)]
+            #vis struct #builder_ident {
+                #(#fields: Option<#field_tys>,)*
+            }
+
+            impl #builder_ident {
+                #(
+                    #[doc = #setter_docs]
+                    #vis fn #setters(mut self, #fields: #param_tys) -> Self {
+                        self.#fields = Some(#inits);
+                        self
+                    }
+                )*
+
+                /// Returns the struct, or the names of all fields that weren't set.
+                #vis fn build(self) -> Result<#type_ident, docxide_template::MissingFields> {
+                    let mut missing = Vec::new();
+                    #(
+                        if self.#fields.is_none() {
+                            missing.push(#field_names);
+                        }
+                    )*
+                    match (#(self.#fields,)*) {
+                        (#(Some(#fields),)*) => Ok(#type_ident { #(#fields),* }),
+                        _ => Err(docxide_template::MissingFields::new(missing)),
+                    }
+                }
+            }
+
+            #[derive(Debug, Clone #serde_derives #(, #derives)*)]
+            #serde_attr
+            #(#attributes)*
+            #vis struct #type_ident {
+                #(#field_attrs #vis #fields: #field_tys,)*
             }
 
             impl docxide_template::__private::Sealed for #type_ident {}
 
             impl #type_ident {
-                pub fn new(#(#fields: impl Into<String>),*) -> Self {
+                #[allow(clippy::too_many_arguments)]
+                #vis fn new(#(#fields: #param_tys),*) -> Self {
                     Self {
-                        #(#fields: #fields.into()),*
+                        #(#fields: #inits),*
                     }
                 }
 
+                #vis fn builder() -> #builder_ident {
+                    // A field named `default` has a setter of that name
+                    <#builder_ident as Default>::default()
+                }
+
+                #from_json
+
                 #save_and_bytes
             }
 
@@ -67,19 +401,27 @@ pub(crate) fn generate_struct(
                     std::path::Path::new(#abs_path_lit)
                 }
 
-                fn replacements(&self) -> Vec<(&str, &str)> {
-                    vec![#( (#replacement_placeholders, self.#replacement_fields.as_str()), )*]
+                fn replacements(&self) -> Vec<(&str, std::borrow::Cow<'_, str>)> {
+                    vec![
+                        #( (#replacement_placeholders, #replacement_values), )*
+                        #escape_replacements
+                    ]
                 }
+
+                #chart_series
             }
         }
     } else {
         quote! {
-            #[derive(Debug, Clone)]
-            pub struct #type_ident;
+            #[derive(Debug, Clone #serde_derives #(, #derives)*)]
+            #serde_attr
+            #(#attributes)*
+            #vis struct #type_ident;
 
             impl docxide_template::__private::Sealed for #type_ident {}
 
             impl #type_ident {
+                #from_json
                 #save_and_bytes
             }
 
@@ -88,10 +430,166 @@ pub(crate) fn generate_struct(
                     std::path::Path::new(#abs_path_lit)
                 }
 
-                fn replacements(&self) -> Vec<(&str, &str)> {
-                    vec![]
+                fn replacements(&self) -> Vec<(&str, std::borrow::Cow<'_, str>)> {
+                    vec![#escape_replacements]
                 }
             }
         }
     }
 }
+
+/// `save`, `to_bytes` and `write_to`, plus their `_with` variants taking
+/// `RenderOptions`, rendering the template at `abs_path_lit`.
+pub(crate) fn render_methods(
+    vis: &syn::Visibility,
+    abs_path_lit: &syn::LitStr,
+    embed: bool,
+    precomputed: &Precomputed,
+) -> proc_macro2::TokenStream {
+    let placeholders = &precomputed.placeholders;
+    let located_parts = precomputed.parts.iter().map(|part| {
+        let name = &part.name;
+        let spans = part.spans.iter().map(|(kind, span)| {
+            let (start, end, text, raw) = (span.start, span.end, &span.text, span.raw);
+            quote! {
+                docxide_template::__private::PrecomputedSpan { kind: #kind, start: #start, end: #end, text: #text, raw: #raw }
+            }
+        });
+        let edits = part.edits.iter().map(|&(span, from, to, placeholder)| {
+            let placeholder = match placeholder {
+                Some(index) => quote! { Some(#index) },
+                None => quote! { None },
+            };
+            quote! {
+                docxide_template::__private::PrecomputedEdit { span: #span, from: #from, to: #to, placeholder: #placeholder }
+            }
+        });
+        quote! {
+            docxide_template::__private::PrecomputedPart { name: #name, spans: &[#(#spans),*], edits: &[#(#edits),*] }
+        }
+    });
+
+    if embed {
+        quote! {
+            const TEMPLATE_BYTES: &'static [u8] = include_bytes!(#abs_path_lit);
+
+            /// The placeholders `PRECOMPUTED` refers to, as `replacements()` lists them.
+            const PLACEHOLDERS: &'static [&'static str] = &[#(#placeholders),*];
+
+            /// Text of the template parts and the placeholders in it, located
+            /// when the template was embedded.
+            const PRECOMPUTED: &'static [docxide_template::__private::PrecomputedPart] = &[#(#located_parts),*];
+
+            fn prepared_template() -> Result<&'static docxide_template::PreparedTemplate, docxide_template::TemplateError> {
+                static PREPARED: docxide_template::__private::PreparedCell = docxide_template::__private::PreparedCell::new();
+                docxide_template::__private::prepared(&PREPARED, Self::TEMPLATE_BYTES, Self::PLACEHOLDERS, Self::PRECOMPUTED)
+            }
+
+            #vis fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), docxide_template::TemplateError> {
+                self.save_with(path, &docxide_template::RenderOptions::default())
+            }
+
+            #vis fn save_with<P: AsRef<std::path::Path>>(
+                &self,
+                path: P,
+                options: &docxide_template::RenderOptions,
+            ) -> Result<(), docxide_template::TemplateError> {
+                Self::prepared_template()?.save(self, path.as_ref().with_extension("docx"), options)
+            }
+
+            #vis fn to_bytes(&self) -> Result<Vec<u8>, docxide_template::TemplateError> {
+                self.to_bytes_with(&docxide_template::RenderOptions::default())
+            }
+
+            #vis fn to_bytes_with(
+                &self,
+                options: &docxide_template::RenderOptions,
+            ) -> Result<Vec<u8>, docxide_template::TemplateError> {
+                Self::prepared_template()?.render(self, options)
+            }
+
+            #vis fn write_to<W: std::io::Write + std::io::Seek>(&self, writer: W) -> Result<(), docxide_template::TemplateError> {
+                self.write_to_with(writer, &docxide_template::RenderOptions::default())
+            }
+
+            #vis fn write_to_with<W: std::io::Write + std::io::Seek>(
+                &self,
+                writer: W,
+                options: &docxide_template::RenderOptions,
+            ) -> Result<(), docxide_template::TemplateError> {
+                Self::prepared_template()?.write_to(self, writer, options)
+            }
+        }
+    } else {
+        quote! {
+            /// Reads the template from disk on every call; to render it many
+            /// times, prepare it once with `PreparedTemplate::from_path`.
+            #vis fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), docxide_template::TemplateError> {
+                self.save_with(path, &docxide_template::RenderOptions::default())
+            }
+
+            #vis fn save_with<P: AsRef<std::path::Path>>(
+                &self,
+                path: P,
+                options: &docxide_template::RenderOptions,
+            ) -> Result<(), docxide_template::TemplateError> {
+                docxide_template::__private::save_docx_with(self, path.as_ref().with_extension("docx"), options)
+            }
+
+            /// Reads the template from disk on every call; to render it many
+            /// times, prepare it once with `PreparedTemplate::from_path`.
+            #vis fn to_bytes(&self) -> Result<Vec<u8>, docxide_template::TemplateError> {
+                self.to_bytes_with(&docxide_template::RenderOptions::default())
+            }
+
+            #vis fn to_bytes_with(
+                &self,
+                options: &docxide_template::RenderOptions,
+            ) -> Result<Vec<u8>, docxide_template::TemplateError> {
+                use docxide_template::DocxTemplate;
+                let template_bytes = std::fs::read(self.template_path())?;
+                docxide_template::__private::build_template_bytes(self, &template_bytes, options)
+            }
+
+            /// Reads the template from disk on every call; to render it many
+            /// times, prepare it once with `PreparedTemplate::from_path`.
+            #vis fn write_to<W: std::io::Write + std::io::Seek>(&self, writer: W) -> Result<(), docxide_template::TemplateError> {
+                self.write_to_with(writer, &docxide_template::RenderOptions::default())
+            }
+
+            #vis fn write_to_with<W: std::io::Write + std::io::Seek>(
+                &self,
+                writer: W,
+                options: &docxide_template::RenderOptions,
+            ) -> Result<(), docxide_template::TemplateError> {
+                use docxide_template::DocxTemplate;
+                let template_bytes = std::fs::read(self.template_path())?;
+                docxide_template::__private::write_template_bytes(self, &template_bytes, writer, options)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive_conflicts_name_the_field() {
+        let fields = vec![format_ident!("customer"), format_ident!("due"), format_ident!("sales")];
+        let field_types = vec![FieldType::Text, FieldType::Date { format: String::new() }, FieldType::ChartSeries];
+        let conflict = |derive: &str, serde| derive_conflict(&syn::parse_str(derive).unwrap(), &fields, &field_types, serde);
+        assert_eq!(conflict("Default", false).as_deref(), Some("`due` field is a `Date`, which has no default value"));
+        assert_eq!(conflict("std::hash::Hash", false).as_deref(), Some("`sales` field is a `Vec<(String, f64)>`"));
+        assert!(conflict("serde::Serialize", false).is_some());
+        assert_eq!(conflict("serde::Serialize", true), None);
+        assert_eq!(conflict("PartialOrd", false), None);
+    }
+
+    #[test]
+    fn builder_setters_leave_build_free() {
+        let fields = vec![format_ident!("build"), format_ident!("set_build"), format_ident!("r#type")];
+        let setters: Vec<String> = builder_setters(&fields).iter().map(ToString::to_string).collect();
+        assert_eq!(setters, ["set_set_build", "set_build", "r#type"]);
+    }
+}
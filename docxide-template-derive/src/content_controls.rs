@@ -0,0 +1,181 @@
+use docxide_template_core::is_wordprocessing;
+use quick_xml::events::Event;
+use quick_xml::NsReader;
+
+use crate::docx_extract::local_attr;
+
+/// Word's default `w:dateFormat` when a date picker doesn't specify one.
+const DEFAULT_DATE_FORMAT: &str = "M/d/yyyy";
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ListItem {
+    pub(crate) display_text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ControlKind {
+    /// `w:dropDownList`: only the listed items are allowed.
+    DropDown(Vec<ListItem>),
+    /// `w:comboBox`: the listed items are suggestions, free text is allowed.
+    ComboBox(Vec<ListItem>),
+    /// `w:date`, with the picker's `w:dateFormat`.
+    Date { format: String },
+}
+
+/// A content control (`w:sdt`) whose properties constrain its value, together
+/// with the text currently inside its `w:sdtContent`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ContentControl {
+    pub(crate) kind: ControlKind,
+    pub(crate) text: String,
+}
+
+struct Frame {
+    kind: Option<ControlKind>,
+    text: String,
+}
+
+/// Collects dropdown, combo box and date picker content controls from a
+/// WordprocessingML part. Other kinds of `w:sdt` are ignored.
+pub(crate) fn collect_content_controls(xml: &str) -> Vec<ContentControl> {
+    let mut reader = NsReader::from_str(xml);
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut controls = Vec::new();
+    let mut in_properties = false;
+    let mut in_text = false;
+
+    loop {
+        let (ns, event) = match reader.read_resolved_event() {
+            Ok((_, Event::Eof)) | Err(_) => break,
+            Ok(event) => event,
+        };
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) if is_wordprocessing(&ns) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                match e.local_name().as_ref() {
+                    b"sdt" if !is_empty => stack.push(Frame { kind: None, text: String::new() }),
+                    b"sdtPr" if !is_empty => in_properties = true,
+                    b"t" if !is_empty => in_text = true,
+                    b"dropDownList" if in_properties => {
+                        if let Some(frame) = stack.last_mut() {
+                            frame.kind = Some(ControlKind::DropDown(Vec::new()));
+                        }
+                    }
+                    b"comboBox" if in_properties => {
+                        if let Some(frame) = stack.last_mut() {
+                            frame.kind = Some(ControlKind::ComboBox(Vec::new()));
+                        }
+                    }
+                    b"listItem" if in_properties => {
+                        let display_text = local_attr(e, b"displayText")
+                            .or_else(|| local_attr(e, b"value"))
+                            .unwrap_or_default();
+                        if let Some(Frame {
+                            kind: Some(ControlKind::DropDown(items) | ControlKind::ComboBox(items)),
+                            ..
+                        }) = stack.last_mut()
+                        {
+                            items.push(ListItem { display_text });
+                        }
+                    }
+                    b"date" if in_properties => {
+                        if let Some(frame) = stack.last_mut() {
+                            frame.kind = Some(ControlKind::Date {
+                                format: DEFAULT_DATE_FORMAT.to_string(),
+                            });
+                        }
+                    }
+                    b"dateFormat" if in_properties => {
+                        if let (Some(Frame { kind: Some(ControlKind::Date { format }), .. }), Some(val)) =
+                            (stack.last_mut(), local_attr(e, b"val"))
+                        {
+                            *format = val;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(ref t) if in_text => {
+                if let (Some(frame), Ok(text)) = (stack.last_mut(), t.unescape()) {
+                    frame.text.push_str(&text);
+                }
+            }
+            Event::End(ref e) if is_wordprocessing(&ns) => match e.local_name().as_ref() {
+                b"sdtPr" => in_properties = false,
+                b"t" => in_text = false,
+                b"sdt" => {
+                    if let Some(frame) = stack.pop() {
+                        if let Some(parent) = stack.last_mut() {
+                            parent.text.push_str(&frame.text);
+                        }
+                        if let Some(kind) = frame.kind {
+                            controls.push(ContentControl { kind, text: frame.text });
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    controls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropdown_items_and_content() {
+        let xml = concat!(
+            r#"<w:sdt><w:sdtPr><w:alias w:val="Status"/><w:dropDownList>"#,
+            r#"<w:listItem w:displayText="Draft" w:value="draft"/>"#,
+            r#"<w:listItem w:displayText="Paid" w:value="paid"/>"#,
+            r#"</w:dropDownList></w:sdtPr>"#,
+            r#"<w:sdtContent><w:r><w:t>{</w:t></w:r><w:r><w:t>status}</w:t></w:r></w:sdtContent></w:sdt>"#,
+        );
+        let controls = collect_content_controls(xml);
+        assert_eq!(
+            controls,
+            vec![ContentControl {
+                kind: ControlKind::DropDown(vec![
+                    ListItem { display_text: "Draft".into() },
+                    ListItem { display_text: "Paid".into() },
+                ]),
+                text: "{status}".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn date_picker_format() {
+        let xml = concat!(
+            r#"<w:sdt><w:sdtPr><w:date><w:dateFormat w:val="dd.MM.yyyy"/><w:lid w:val="nb-NO"/></w:date></w:sdtPr>"#,
+            r#"<w:sdtContent><w:r><w:t>{due}</w:t></w:r></w:sdtContent></w:sdt>"#,
+        );
+        let controls = collect_content_controls(xml);
+        assert_eq!(controls.len(), 1);
+        assert_eq!(controls[0].kind, ControlKind::Date { format: "dd.MM.yyyy".into() });
+        assert_eq!(controls[0].text, "{due}");
+    }
+
+    #[test]
+    fn controls_are_matched_by_namespace() {
+        let xml = concat!(
+            r#"<doc xmlns:x="http://purl.oclc.org/ooxml/wordprocessingml/main">"#,
+            r#"<x:sdt><x:sdtPr><x:comboBox><x:listItem x:displayText="Open"/></x:comboBox></x:sdtPr>"#,
+            r#"<x:sdtContent><x:r><x:t>{state}</x:t></x:r></x:sdtContent></x:sdt></doc>"#,
+        );
+        let controls = collect_content_controls(xml);
+        assert_eq!(controls.len(), 1);
+        assert_eq!(controls[0].kind, ControlKind::ComboBox(vec![ListItem { display_text: "Open".into() }]));
+        assert_eq!(controls[0].text, "{state}");
+    }
+
+    #[test]
+    fn plain_text_controls_are_ignored() {
+        let xml = r#"<w:sdt><w:sdtPr><w:text/></w:sdtPr><w:sdtContent><w:r><w:t>{name}</w:t></w:r></w:sdtContent></w:sdt>"#;
+        assert!(collect_content_controls(xml).is_empty());
+    }
+}
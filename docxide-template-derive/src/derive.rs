@@ -0,0 +1,189 @@
+//! `#[derive(DocxTemplate)]`, for structs written by hand and checked against
+//! their template.
+
+use std::fs;
+use std::path::PathBuf;
+
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+use crate::args::{field_rename, DeriveArgs};
+use crate::codegen::render_methods;
+use crate::docx_extract::is_valid_docx_file;
+use crate::naming::FieldNaming;
+use crate::placeholders::FieldType;
+use crate::precompute::{precompute, Precomputed};
+use crate::template_content;
+
+/// Implements `DocxTemplate` and the render methods for `input`, whose fields
+/// must fill the template's placeholders exactly: every field a placeholder,
+/// every placeholder a field.
+pub(crate) fn derive_docx_template(input: &DeriveInput, embed: bool) -> syn::Result<proc_macro2::TokenStream> {
+    let args = DeriveArgs::from_attributes(&input.ident, &input.attrs)?;
+    let struct_fields: Vec<&syn::Field> = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new(input.ident.span(), "DocxTemplate needs a struct with named fields"))
+            }
+        },
+        _ => return Err(syn::Error::new(input.ident.span(), "DocxTemplate can only be derived for structs")),
+    };
+
+    let path = PathBuf::from(args.path.value());
+    if !is_valid_docx_file(&path) {
+        return Err(syn::Error::new(args.path.span(), format!("{:?} is not a .docx file", path)));
+    }
+    let buf = fs::read(&path)
+        .map_err(|e| syn::Error::new(args.path.span(), format!("failed to read template {:?}: {}", path, e)))?;
+    let content = template_content(&buf, &args.delimiters, &FieldNaming::default())
+        .ok_or_else(|| syn::Error::new(args.path.span(), format!("unable to read docx content of {:?}", path)))?;
+
+    // The struct field filling each of the template's fields
+    let mut members: Vec<Option<&syn::Field>> = vec![None; content.fields.len()];
+    let mut errors: Vec<syn::Error> = Vec::new();
+    for field in &struct_fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let rename = field_rename(&field.attrs)?;
+        let name = rename.as_ref().map_or_else(|| ident.unraw().to_string(), LitStr::value);
+        let span = rename.as_ref().map_or_else(|| ident.span(), LitStr::span);
+        let index = content
+            .fields
+            .iter()
+            .zip(&content.aliases)
+            .position(|(f, aliases)| f.unraw() == name || (rename.is_some() && aliases.contains(&name)));
+        match index {
+            None => errors.push(syn::Error::new(span, format!("no placeholder `{}` in {:?}", name, path))),
+            Some(i) => match members[i] {
+                Some(other) => errors.push(syn::Error::new(
+                    span,
+                    format!("placeholder `{}` is already filled by `{}`", name, other.ident.as_ref().unwrap()),
+                )),
+                None => members[i] = Some(field),
+            },
+        }
+    }
+    let unfilled: Vec<String> = content
+        .fields
+        .iter()
+        .zip(&content.aliases)
+        .zip(&members)
+        .filter(|(_, member)| member.is_none())
+        .map(|((field, aliases), _)| format!("`{}`", aliases.first().cloned().unwrap_or_else(|| field.unraw().to_string())))
+        .collect();
+    if !unfilled.is_empty() {
+        errors.push(syn::Error::new(
+            input.ident.span(),
+            format!(
+                "placeholders without a field in {:?}: {}; add fields for them, or rename fields with #[docx(rename = \"...\")]",
+                path,
+                unfilled.join(", ")
+            ),
+        ));
+    }
+    if let Some(error) = errors.into_iter().reduce(|mut all, error| {
+        all.combine(error);
+        all
+    }) {
+        return Err(error);
+    }
+
+    let member_of = |template_field: &proc_macro2::Ident| {
+        let index = content.fields.iter().position(|f| f == template_field).expect("template field");
+        (members[index].expect("filled field"), &content.field_types[index])
+    };
+
+    // A dropdown takes a type whose every value is in its list. The checks are
+    // an associated constant, so they can name the struct's type parameters,
+    // and `replacements` refers to it to have it evaluated.
+    let dropdown_checks: Vec<_> = content.fields.iter().zip(&content.field_types).filter_map(|(template_field, field_type)| {
+        let FieldType::Choice { variants, open: false } = field_type else {
+            return None;
+        };
+        let (field, _) = member_of(template_field);
+        let ty = &field.ty;
+        let entries = variants.iter().map(|variant| variant.display_text.as_str());
+        Some(quote_spanned! { ty.span()=>
+            docxide_template::__private::check_dropdown::<#ty>(&[#(#entries),*]);
+        })
+    }).collect();
+    let (dropdown_const, dropdown_use) = if dropdown_checks.is_empty() {
+        (quote! {}, quote! {})
+    } else {
+        (
+            quote! {
+                #[doc(hidden)]
+                const __DOCXIDE_DROPDOWNS: () = { #(#dropdown_checks)* };
+            },
+            quote! { let () = Self::__DOCXIDE_DROPDOWNS; },
+        )
+    };
+
+    // Any `Display` type fills text and combo boxes
+    let replacement_values = content.replacement_fields.iter().map(|template_field| {
+        let (field, field_type) = member_of(template_field);
+        let member = &field.ident;
+        match field_type {
+            FieldType::Date { format } => {
+                quote_spanned! { field.ty.span()=> std::borrow::Cow::Owned(self.#member.format(#format)) }
+            }
+            _ => quote_spanned! { field.ty.span()=> std::borrow::Cow::Owned(std::string::ToString::to_string(&self.#member)) },
+        }
+    });
+    let replacement_placeholders = &content.replacement_placeholders;
+    let escape_sequences = content.escapes.iter().map(|(escape, _)| escape);
+    let escape_literals = content.escapes.iter().map(|(_, literal)| literal);
+
+    let chart_series = if content.chart_fields.is_empty() {
+        quote! {}
+    } else {
+        let chart_placeholders = &content.chart_placeholders;
+        let chart_values = content.chart_fields.iter().map(|template_field| {
+            let (field, _) = member_of(template_field);
+            let member = &field.ident;
+            quote_spanned! { field.ty.span()=> std::convert::AsRef::<[(String, f64)]>::as_ref(&self.#member) }
+        });
+        quote! {
+            fn chart_series(&self) -> Vec<(&str, &[(String, f64)])> {
+                vec![
+                    #( (#chart_placeholders, #chart_values), )*
+                ]
+            }
+        }
+    };
+
+    let abs_path = path.canonicalize().expect("Failed to canonicalize template path");
+    let abs_path_lit = LitStr::new(abs_path.to_str().expect("Failed to convert path to string"), args.path.span());
+    let precomputed = if embed { precompute(&buf, content.replacement_patterns()) } else { Precomputed::default() };
+    let methods = render_methods(&input.vis, &abs_path_lit, embed, &precomputed);
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics docxide_template::__private::Sealed for #ident #ty_generics #where_clause {}
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #dropdown_const
+            #methods
+        }
+
+        impl #impl_generics docxide_template::DocxTemplate for #ident #ty_generics #where_clause {
+            fn template_path(&self) -> &std::path::Path {
+                std::path::Path::new(#abs_path_lit)
+            }
+
+            fn replacements(&self) -> Vec<(&str, std::borrow::Cow<'_, str>)> {
+                #dropdown_use
+                vec![
+                    #( (#replacement_placeholders, #replacement_values), )*
+                    #( (#escape_sequences, std::borrow::Cow::Borrowed(#escape_literals)), )*
+                ]
+            }
+
+            #chart_series
+        }
+    })
+}
@@ -1,86 +1,393 @@
-use docx_rs::{
-    DocumentChild, FooterChild, HeaderChild, StructuredDataTagChild, Table, TableCellContent,
-    TableChild, TableRowChild,
-};
 use file_format::FileFormat;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{NsReader, Reader};
+use docxide_template_core::{decode_percent_braces, is_wordprocessing};
+use std::io::{Cursor, Read};
 use std::path::Path;
 
-pub(crate) fn collect_text_from_document_children(children: Vec<DocumentChild>) -> Vec<String> {
+/// Parts besides the main document body whose paragraphs may hold placeholders.
+pub(crate) const NOTE_PARTS: [&str; 3] = ["word/footnotes.xml", "word/endnotes.xml", "word/comments.xml"];
+
+/// Collects the text of every paragraph (`w:p`) in a WordprocessingML part.
+///
+/// Paragraphs nested inside text boxes (`w:txbxContent`) are collected on their
+/// own, after the top-level ones, and both branches of `mc:AlternateContent` are
+/// read, so a text box stored twice yields its paragraphs twice.
+pub(crate) fn collect_paragraph_texts(xml: &str) -> Vec<String> {
+    let (mut texts, nested) = collect_paragraph_texts_by_nesting(xml);
+    texts.extend(nested);
+    texts
+}
+
+/// Like [`collect_paragraph_texts`], with the top-level paragraphs and those
+/// nested in text boxes apart.
+pub(crate) fn collect_paragraph_texts_by_nesting(xml: &str) -> (Vec<String>, Vec<String>) {
+    let mut reader = NsReader::from_str(xml);
+    let mut open_paragraphs: Vec<String> = Vec::new();
     let mut texts = Vec::new();
-    for child in children {
-        match child {
-            DocumentChild::Paragraph(p) => texts.push(p.raw_text()),
-            DocumentChild::Table(t) => texts.extend(collect_text_from_table(&t)),
-            DocumentChild::StructuredDataTag(sdt) => {
-                texts.extend(collect_text_from_sdt_children(&sdt.children));
+    let mut nested = Vec::new();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_resolved_event() {
+            Ok((ns, Event::Start(e))) if is_wordprocessing(&ns) => match e.local_name().as_ref() {
+                b"p" => open_paragraphs.push(String::new()),
+                b"t" => in_text = true,
+                _ => {}
+            },
+            Ok((ns, Event::End(e))) if is_wordprocessing(&ns) => match e.local_name().as_ref() {
+                b"p" => {
+                    let paragraph = open_paragraphs.pop();
+                    if open_paragraphs.is_empty() {
+                        texts.extend(paragraph);
+                    } else {
+                        nested.extend(paragraph);
+                    }
+                }
+                b"t" => in_text = false,
+                _ => {}
+            },
+            Ok((_, Event::Text(t))) if in_text => {
+                if let (Some(paragraph), Ok(text)) = (open_paragraphs.last_mut(), t.unescape()) {
+                    paragraph.push_str(&text);
+                }
             }
+            Ok((_, Event::Eof)) | Err(_) => break,
             _ => {}
         }
     }
-    texts
+    (texts, nested)
 }
 
-pub(crate) fn collect_text_from_table(table: &Table) -> Vec<String> {
+/// Collects text outside of `w:t` that may hold placeholders: the field
+/// instructions (`w:instrText`) of each paragraph, joined like run text, and the
+/// alt text (`descr`) and title of drawings (`wp:docPr`).
+pub(crate) fn collect_field_and_alt_texts(xml: &str) -> Vec<String> {
+    let mut reader = NsReader::from_str(xml);
+    let mut open_paragraphs: Vec<String> = Vec::new();
     let mut texts = Vec::new();
-    for row in &table.rows {
-        let TableChild::TableRow(ref row) = row;
-        for cell in &row.cells {
-            let TableRowChild::TableCell(ref cell) = cell;
-            for content in &cell.children {
-                match content {
-                    TableCellContent::Paragraph(p) => texts.push(p.raw_text()),
-                    TableCellContent::Table(t) => texts.extend(collect_text_from_table(t)),
-                    _ => {}
+    let mut in_instruction = false;
+
+    loop {
+        match reader.read_resolved_event() {
+            Ok((_, Event::Start(e) | Event::Empty(e))) if e.local_name().as_ref() == b"docPr" => {
+                texts.extend(attr(&e, b"descr").into_iter().chain(attr(&e, b"title")));
+            }
+            Ok((ns, Event::Start(e))) if is_wordprocessing(&ns) => match e.local_name().as_ref() {
+                b"p" => open_paragraphs.push(String::new()),
+                b"instrText" => in_instruction = true,
+                _ => {}
+            },
+            Ok((ns, Event::End(e))) if is_wordprocessing(&ns) => match e.local_name().as_ref() {
+                b"p" => texts.extend(open_paragraphs.pop().filter(|t| !t.is_empty())),
+                b"instrText" => in_instruction = false,
+                _ => {}
+            },
+            Ok((_, Event::Text(t))) if in_instruction => {
+                if let (Some(paragraph), Ok(text)) = (open_paragraphs.last_mut(), t.unescape()) {
+                    paragraph.push_str(&text);
                 }
             }
+            Ok((_, Event::Eof)) | Err(_) => break,
+            _ => {}
         }
     }
     texts
 }
 
-fn collect_text_from_sdt_children(children: &[StructuredDataTagChild]) -> Vec<String> {
-    let mut texts = Vec::new();
-    for child in children {
-        match child {
-            StructuredDataTagChild::Paragraph(p) => texts.push(p.raw_text()),
-            StructuredDataTagChild::Table(t) => texts.extend(collect_text_from_table(t)),
-            StructuredDataTagChild::StructuredDataTag(sdt) => {
-                texts.extend(collect_text_from_sdt_children(&sdt.children));
+/// Collects the targets of external relationships (hyperlinks) in every
+/// `word/_rels/*.rels` part, with Word's percent-encoded braces decoded.
+pub(crate) fn collect_external_targets(buf: &[u8]) -> Vec<String> {
+    let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(buf)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.starts_with("word/_rels/") && name.ends_with(".rels"))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+
+    let mut targets = Vec::new();
+    for name in names {
+        let Ok(mut file) = archive.by_name(&name) else {
+            continue;
+        };
+        let mut xml = String::new();
+        if file.read_to_string(&mut xml).is_err() {
+            continue;
+        }
+        targets.extend(external_targets(&xml));
+    }
+    targets
+}
+
+fn external_targets(rels_xml: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut reader = Reader::from_str(rels_xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e))
+                if e.local_name().as_ref() == b"Relationship"
+                    && attr(&e, b"TargetMode").is_some_and(|m| m == "External") =>
+            {
+                targets.extend(attr(&e, b"Target").map(|t| decode_percent_braces(&t).0));
             }
+            Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
-    texts
+    targets
 }
 
-pub(crate) fn collect_text_from_header_children(children: &[HeaderChild]) -> Vec<String> {
-    let mut texts = Vec::new();
-    for child in children {
-        match child {
-            HeaderChild::Paragraph(p) => texts.push(p.raw_text()),
-            HeaderChild::Table(t) => texts.extend(collect_text_from_table(t)),
-            HeaderChild::StructuredDataTag(sdt) => {
-                texts.extend(collect_text_from_sdt_children(&sdt.children));
+/// Reads a single part of a `.docx` as an XML string.
+pub(crate) fn read_xml_part(buf: &[u8], name: &str) -> Option<String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(buf)).ok()?;
+    let mut file = archive.by_name(name).ok()?;
+    let mut xml = String::new();
+    file.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+/// Main document part.
+pub(crate) const DOCUMENT_PART: &str = "word/document.xml";
+
+const DOCUMENT_RELS_PART: &str = "word/_rels/document.xml.rels";
+const HEADER_REL_TYPE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/header";
+const FOOTER_REL_TYPE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer";
+
+fn attr(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+/// The value of the attribute with `local` name, whatever its prefix.
+pub(crate) fn local_attr(e: &BytesStart, local: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == local)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+/// Resolves a relationship target in `document.xml.rels` to a part name.
+fn resolve_target(target: &str) -> String {
+    match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("word/{}", target),
+    }
+}
+
+/// Names of all header and footer parts of every section, whether default,
+/// first-page or even-page, as those of the final section and the others.
+///
+/// The final section's parts are its headers, then its footers, each default,
+/// first-page and even-page. The others are listed in the order their
+/// `w:headerReference`/`w:footerReference` appear in the document, followed by
+/// any related but unreferenced ones.
+pub(crate) fn header_footer_parts(rels_xml: &str, document_xml: &str) -> (Vec<String>, Vec<String>) {
+    let mut targets: Vec<(String, String)> = Vec::new();
+    let mut reader = Reader::from_str(rels_xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e) | Event::Empty(e)) if e.local_name().as_ref() == b"Relationship" => {
+                let rel_type = attr(&e, b"Type").unwrap_or_default();
+                let external = attr(&e, b"TargetMode").is_some_and(|m| m == "External");
+                if external || (rel_type != HEADER_REL_TYPE && rel_type != FOOTER_REL_TYPE) {
+                    continue;
+                }
+                if let (Some(id), Some(target)) = (attr(&e, b"Id"), attr(&e, b"Target")) {
+                    targets.push((id, resolve_target(&target)));
+                }
             }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
         }
     }
-    texts
+
+    // Every reference as (is footer, type, part), and those of the last `w:sectPr`
+    let mut references: Vec<(bool, String, String)> = Vec::new();
+    let mut section: Vec<(bool, String, String)> = Vec::new();
+    let mut reader = NsReader::from_str(document_xml);
+    loop {
+        match reader.read_resolved_event() {
+            Ok((ns, Event::Start(e))) if is_wordprocessing(&ns) && e.local_name().as_ref() == b"sectPr" => {
+                section.clear()
+            }
+            Ok((ns, Event::Start(e) | Event::Empty(e)))
+                if is_wordprocessing(&ns)
+                    && matches!(e.local_name().as_ref(), b"headerReference" | b"footerReference") =>
+            {
+                let Some(id) = local_attr(&e, b"id") else { continue };
+                if let Some((_, part)) = targets.iter().find(|(rel_id, _)| *rel_id == id) {
+                    let is_footer = e.local_name().as_ref() == b"footerReference";
+                    let kind = local_attr(&e, b"type").unwrap_or_else(|| "default".to_string());
+                    references.push((is_footer, kind.clone(), part.clone()));
+                    section.push((is_footer, kind, part.clone()));
+                }
+            }
+            Ok((_, Event::Eof)) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let mut final_section: Vec<String> = Vec::new();
+    for is_footer in [false, true] {
+        for kind in ["default", "first", "even"] {
+            let parts = section.iter().filter(|(footer, k, _)| *footer == is_footer && k == kind);
+            for (_, _, part) in parts {
+                if !final_section.contains(part) {
+                    final_section.push(part.clone());
+                }
+            }
+        }
+    }
+
+    let mut others: Vec<String> = Vec::new();
+    let referenced = references.into_iter().map(|(_, _, part)| part);
+    for part in referenced.chain(targets.into_iter().map(|(_, part)| part)) {
+        if !final_section.contains(&part) && !others.contains(&part) {
+            others.push(part);
+        }
+    }
+    (final_section, others)
+}
+
+/// Parts of a `.docx` as `(name, xml)`.
+pub(crate) type XmlParts = Vec<(String, String)>;
+
+/// Reads every header and footer part of a `.docx`, split like
+/// [`header_footer_parts`].
+pub(crate) fn read_header_footer_parts(buf: &[u8], document_xml: &str) -> (XmlParts, XmlParts) {
+    let rels = read_xml_part(buf, DOCUMENT_RELS_PART).unwrap_or_default();
+    let (final_section, others) = header_footer_parts(&rels, document_xml);
+    let read = |names: Vec<String>| -> XmlParts {
+        names
+            .into_iter()
+            .filter_map(|name| Some((name.clone(), read_xml_part(buf, &name)?)))
+            .collect()
+    };
+    (read(final_section), read(others))
 }
 
-pub(crate) fn collect_text_from_footer_children(children: &[FooterChild]) -> Vec<String> {
+/// Collects the text of every element in the document property parts
+/// (`docProps/core.xml`, `app.xml` and `custom.xml`), e.g. title and keywords.
+pub(crate) fn collect_text_from_properties(buf: &[u8]) -> Vec<String> {
+    let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(buf)) else {
+        return Vec::new();
+    };
     let mut texts = Vec::new();
-    for child in children {
-        match child {
-            FooterChild::Paragraph(p) => texts.push(p.raw_text()),
-            FooterChild::Table(t) => texts.extend(collect_text_from_table(t)),
-            FooterChild::StructuredDataTag(sdt) => {
-                texts.extend(collect_text_from_sdt_children(&sdt.children));
+    for name in ["docProps/core.xml", "docProps/app.xml", "docProps/custom.xml"] {
+        let Ok(mut file) = archive.by_name(name) else {
+            continue;
+        };
+        let mut xml = String::new();
+        if file.read_to_string(&mut xml).is_err() {
+            continue;
+        }
+        let mut reader = Reader::from_str(&xml);
+        loop {
+            match reader.read_event() {
+                Ok(Event::Text(t)) => {
+                    if let Ok(text) = t.unescape() {
+                        texts.push(text.into_owned());
+                    }
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
             }
         }
     }
     texts
 }
 
+/// The value of the custom document property `name` in `docProps/custom.xml`.
+pub(crate) fn custom_property(custom_xml: &str, name: &str) -> Option<String> {
+    let mut reader = Reader::from_str(custom_xml);
+    let mut value: Option<String> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"property" => {
+                value = (attr(&e, b"name").as_deref() == Some(name)).then(String::new);
+            }
+            Ok(Event::Text(t)) => {
+                if let (Some(value), Ok(text)) = (value.as_mut(), t.unescape()) {
+                    value.push_str(&text);
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"property" && value.is_some() => return value,
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+    }
+}
+
+/// Collects the name of every chart series (`c:ser/c:tx`) in the charts of a
+/// `.docx`, charts in part name order.
+pub(crate) fn collect_chart_series_names(buf: &[u8]) -> Vec<String> {
+    let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(buf)) else {
+        return Vec::new();
+    };
+    let mut charts: Vec<String> = archive
+        .file_names()
+        .filter(|name| {
+            name.strip_prefix("word/charts/chart")
+                .is_some_and(|rest| rest.ends_with(".xml") && !rest.contains('/'))
+        })
+        .map(str::to_string)
+        .collect();
+    charts.sort_by_key(|name| (name.len(), name.clone()));
+
+    let mut names = Vec::new();
+    for chart in charts {
+        let Ok(mut file) = archive.by_name(&chart) else {
+            continue;
+        };
+        let mut xml = String::new();
+        if file.read_to_string(&mut xml).is_err() {
+            continue;
+        }
+        names.extend(series_names(&xml));
+    }
+    names
+}
+
+fn series_names(chart_xml: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(chart_xml);
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    // Depth of the `c:tx` element of the series being read, if inside one.
+    let mut name_depth = None;
+    let mut names = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                if e.name().as_ref() == b"c:tx" && path.last().is_some_and(|p| p == b"c:ser") {
+                    name_depth = Some(path.len());
+                    names.push(String::new());
+                }
+                path.push(e.name().as_ref().to_vec());
+            }
+            Ok(Event::End(_)) => {
+                path.pop();
+                if name_depth == Some(path.len()) {
+                    name_depth = None;
+                }
+            }
+            Ok(Event::Text(t)) if name_depth.is_some() && path.last().is_some_and(|p| p == b"c:v") => {
+                if let (Some(name), Ok(text)) = (names.last_mut(), t.unescape()) {
+                    name.push_str(&text);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    names
+}
+
 pub(crate) fn print_docxide_message(message: &str, path: &Path) {
     println!("\x1b[34m[Docxide-template]\x1b[0m {} {:?}", message, path);
 }
@@ -92,3 +399,100 @@ pub(crate) fn is_valid_docx_file(path: &Path) -> bool {
 
     matches!(FileFormat::from_file(path), Ok(fmt) if fmt.extension() == "docx")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paragraph_texts_join_runs() {
+        let xml = r#"<w:body><w:p><w:r><w:t>{first</w:t></w:r><w:r><w:t>_name}</w:t></w:r></w:p><w:p/></w:body>"#;
+        assert_eq!(collect_paragraph_texts(xml), vec!["{first_name}"]);
+    }
+
+    #[test]
+    fn paragraph_texts_with_other_prefix() {
+        let xml = concat!(
+            r#"<x:body xmlns:x="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+            r#"<x:p><x:r><x:tab/><x:t>{na</x:t></x:r><x:r><x:t>me}</x:t></x:r></x:p></x:body>"#,
+        );
+        assert_eq!(collect_paragraph_texts(xml), vec!["{name}"]);
+    }
+
+    #[test]
+    fn header_footer_parts_from_every_section() {
+        let rels = concat!(
+            r#"<Relationships>"#,
+            r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+            r#"<Relationship Id="rId7" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer" Target="footer1.xml"/>"#,
+            r#"<Relationship Id="rId8" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="/word/header2.xml"/>"#,
+            r#"<Relationship Id="rId9" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header1.xml"/>"#,
+            r#"<Relationship Id="rId10" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/header" Target="header3.xml"/>"#,
+            r#"</Relationships>"#,
+        );
+        let document = concat!(
+            r#"<w:body><w:p><w:pPr><w:sectPr><w:headerReference w:type="default" r:id="rId9"/>"#,
+            r#"<w:footerReference w:type="default" r:id="rId7"/></w:sectPr></w:pPr></w:p>"#,
+            r#"<w:sectPr><w:headerReference w:type="first" r:id="rId8"/>"#,
+            r#"<w:footerReference w:type="default" r:id="rId7"/></w:sectPr></w:body>"#,
+        );
+        let (final_section, others) = header_footer_parts(rels, document);
+        assert_eq!(final_section, vec!["word/header2.xml", "word/footer1.xml"]);
+        assert_eq!(others, vec!["word/header1.xml", "word/header3.xml"]);
+    }
+
+    #[test]
+    fn field_instructions_and_alt_text() {
+        let xml = concat!(
+            r#"<w:p><w:r><w:instrText> HYPERLINK "{docs</w:instrText></w:r><w:r><w:instrText>_url}" </w:instrText></w:r>"#,
+            r#"<w:r><w:t>{ignored}</w:t></w:r></w:p>"#,
+            r#"<w:p><w:r><w:drawing><wp:inline><wp:docPr id="1" name="Picture 1" descr="Photo of {person}" title="{photo_title}"/>"#,
+            r#"</wp:inline></w:drawing></w:r></w:p>"#,
+        );
+        assert_eq!(
+            collect_field_and_alt_texts(xml),
+            vec![r#" HYPERLINK "{docs_url}" "#, "Photo of {person}", "{photo_title}"]
+        );
+    }
+
+    #[test]
+    fn external_targets_are_decoded() {
+        let rels = concat!(
+            r#"<Relationships>"#,
+            r#"<Relationship Id="rId1" Type="header" Target="header1.xml"/>"#,
+            r#"<Relationship Id="rId2" Type="hyperlink" Target="https://example.com/%7Bslug%7D" TargetMode="External"/>"#,
+            r#"</Relationships>"#,
+        );
+        assert_eq!(external_targets(rels), vec!["https://example.com/{slug}"]);
+    }
+
+    #[test]
+    fn custom_property_value() {
+        let xml = r#"<Properties><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Other"><vt:lpwstr>x</vt:lpwstr></property><property pid="3" name="DocxideFieldOrder"><vt:lpwstr>company, first name</vt:lpwstr></property></Properties>"#;
+        assert_eq!(custom_property(xml, "DocxideFieldOrder").as_deref(), Some("company, first name"));
+        assert_eq!(custom_property(xml, "Missing"), None);
+    }
+
+    #[test]
+    fn chart_series_names() {
+        let xml = concat!(
+            r#"<c:chartSpace><c:chart><c:title><c:tx><c:rich><a:p><a:r><a:t>Title</a:t></a:r></a:p></c:rich></c:tx></c:title>"#,
+            r#"<c:plotArea><c:barChart><c:ser><c:tx><c:strRef><c:f>Sheet1!$B$1</c:f><c:strCache><c:pt idx="0">"#,
+            r#"<c:v>Revenue {revenue}</c:v></c:pt></c:strCache></c:strRef></c:tx>"#,
+            r#"<c:cat><c:strRef><c:strCache><c:pt idx="0"><c:v>Jan</c:v></c:pt></c:strCache></c:strRef></c:cat></c:ser>"#,
+            r#"<c:ser><c:tx><c:v>{visitors}</c:v></c:tx></c:ser></c:barChart></c:plotArea></c:chart></c:chartSpace>"#,
+        );
+        assert_eq!(series_names(xml), vec!["Revenue {revenue}", "{visitors}"]);
+    }
+
+    #[test]
+    fn text_box_paragraphs_are_separate() {
+        let xml = concat!(
+            r#"<w:p><w:r><w:t>Before </w:t></w:r><w:r><mc:AlternateContent>"#,
+            r#"<mc:Choice><w:drawing><wps:txbx><w:txbxContent><w:p><w:r><w:t>{label}</w:t></w:r></w:p></w:txbxContent></wps:txbx></w:drawing></mc:Choice>"#,
+            r#"<mc:Fallback><w:pict><v:textbox><w:txbxContent><w:p><w:r><w:t>{label}</w:t></w:r></w:p></w:txbxContent></v:textbox></w:pict></mc:Fallback>"#,
+            r#"</mc:AlternateContent></w:r><w:r><w:t>after</w:t></w:r></w:p>"#,
+        );
+        assert_eq!(collect_paragraph_texts(xml), vec!["Before after", "{label}", "{label}"]);
+    }
+}
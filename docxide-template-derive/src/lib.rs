@@ -1,27 +1,34 @@
 extern crate proc_macro;
+mod args;
 mod codegen;
+mod content_controls;
+mod derive;
 mod docx_extract;
 mod naming;
 mod placeholders;
+mod precompute;
 
-use docx_rs::read_docx;
 use proc_macro::TokenStream;
 use quote::quote;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs,
     path::PathBuf,
 };
 
-use syn::{parse_str, LitStr};
+use syn::{parse_macro_input, parse_str};
 
-use codegen::generate_struct;
+use args::TemplateArgs;
+use codegen::{builder_setters, companion_type_names, generate_struct};
+use content_controls::collect_content_controls;
 use docx_extract::{
-    collect_text_from_document_children, collect_text_from_footer_children,
-    collect_text_from_header_children, is_valid_docx_file, print_docxide_message,
+    collect_chart_series_names, collect_external_targets, collect_field_and_alt_texts, collect_paragraph_texts,
+    collect_paragraph_texts_by_nesting, collect_text_from_properties, custom_property, is_valid_docx_file,
+    print_docxide_message, read_header_footer_parts, read_xml_part, DOCUMENT_PART, NOTE_PARTS,
 };
-use naming::derive_type_name_from_filename;
-use placeholders::generate_struct_content;
+use naming::{derive_type_name_from_filename, Collisions, FieldNaming};
+use placeholders::{generate_struct_content, Collision, Delimiters, SourceText, StructContent, FIELD_ORDER_PROPERTY};
+use precompute::{precompute, Precomputed};
 
 /// Scans a directory for `.docx` template files and generates a typed struct for each one.
 ///
@@ -30,6 +37,23 @@ use placeholders::generate_struct_content;
 ///
 /// With the `embed` feature enabled, template bytes are baked into the binary via
 /// `include_bytes!`, making it fully self-contained with no runtime file dependencies.
+/// The location of the template text is worked out at compile time as well.
+///
+/// Besides `delimiters = ("{{", "}}")`, the macro takes `derive = (PartialEq, ...)`,
+/// `attributes = (#[...], ...)` and `visibility = pub(crate)`, applied to every
+/// generated struct and dropdown enum, and `order = document | alphabetical | declared` for the
+/// order of fields and constructor parameters. A template's `DocxideFieldOrder`
+/// custom property, listing its placeholder names, overrides the order.
+/// `rename = ("ZIPCODE" => zip_code, ...)` names the field a placeholder fills,
+/// merging placeholders renamed to the same field, and `Invoice = ("Total" => amount)`
+/// inside it does so for the template generating `Invoice` only. Placeholders named after
+/// keywords become raw identifiers like `r#type`, or take the suffix given with
+/// `keyword_suffix = "_value"`. `collisions = merge | error | suffix` decides what
+/// happens to placeholders spelled differently that make the same field name.
+///
+/// With the `serde` feature enabled, generated structs and enums derive `Serialize`
+/// and `Deserialize`, and get a `from_json()` constructor. Fields also accept the
+/// placeholder names as written in the template, e.g. `firstName` for `first_name`.
 ///
 /// # Usage
 ///
@@ -39,21 +63,51 @@ use placeholders::generate_struct_content;
 /// generate_templates!("path/to/templates");
 /// ```
 ///
+/// Placeholders are delimited by `{` and `}` unless configured otherwise. Use the
+/// `delimiters` option when templates contain literal braces, e.g. JSON or code samples:
+///
+/// ```rust,ignore
+/// generate_templates!("path/to/templates", delimiters = ("{{", "}}"));
+/// generate_templates!("path/to/templates", delimiters = ("«", "»"));
+/// ```
+///
+/// A backslash before a delimiter (`\{`, `\}`) renders the delimiter literally and is
+/// never treated as the start or end of a placeholder.
+///
 /// For each `.docx` file, this generates a struct with:
-/// - A field for each `{placeholder}` found in the document text (converted to snake_case)
-/// - `new()` constructor taking all field values as `impl Into<String>`
+/// - A field for each `{placeholder}` found in the document text (body, text boxes,
+///   headers, footers, footnotes, endnotes and comments), image alt text, hyperlink
+///   addresses, field codes or document properties (title, subject, keywords,
+///   custom properties, ...), converted to snake_case
+/// - An enum per placeholder inside a dropdown or combo box content control, and a
+///   `docxide_template::Date` field for one inside a date picker
+/// - A `Vec<(String, f64)>` field per placeholder in a chart series name, filling the
+///   series' categories and values
+/// - `new()` constructor taking text fields as `impl Into<String>` and enum, date and
+///   chart fields as their own types
+/// - `builder()` returning a `{Name}Builder` with a setter per field, whose `build()`
+///   returns `Err(MissingFields)` naming any field that wasn't set. A field named
+///   `build` is set with `set_build()`
 /// - `save(path)` to write a filled-in `.docx` to disk
 /// - `to_bytes()` to get the filled-in `.docx` as `Vec<u8>`
+/// - `write_to(writer)` to write the filled-in `.docx` to any `Write + Seek` sink
+/// - `save_with(path, &options)`, `to_bytes_with(&options)` and
+///   `write_to_with(writer, &options)`, which also apply a
+///   `RenderOptions`, e.g. to set document properties like the title or author
 #[proc_macro]
 pub fn generate_templates(input: TokenStream) -> TokenStream {
     let embed = cfg!(feature = "embed");
+    let serde = cfg!(feature = "serde");
 
-    let lit: LitStr = syn::parse(input).expect("expected a string literal, e.g. generate_templates!(\"path/to/templates\")");
-    let folder_path = lit.value();
+    let args = parse_macro_input!(input as TemplateArgs);
+    let folder_path = args.path.value();
 
     let paths = fs::read_dir(&folder_path).unwrap_or_else(|e| panic!("Failed to read template directory {:?}: {}", folder_path, e));
     let mut structs = Vec::new();
     let mut seen_type_names: HashMap<String, PathBuf> = HashMap::new();
+    let mut templates: HashSet<String> = HashSet::new();
+    // `rename` keys matched in some template, scoped ones with their template
+    let mut used_renames: HashSet<(Option<String>, String)> = HashSet::new();
 
     for path in paths {
         let path = path.expect("Failed to read path").path();
@@ -88,7 +142,7 @@ pub fn generate_templates(input: TokenStream) -> TokenStream {
 
         if let Some(existing_path) = seen_type_names.get(&type_name) {
             panic!(
-                "\n\n[Docxide-template] Type name collision: both {:?} and {:?} produce the struct name `{}`.\n\
+                "\n\n[Docxide-template] Type name collision: both {:?} and {:?} produce the type `{}`.\n\
                 Rename one of the files to avoid this conflict.\n",
                 existing_path, path, type_name
             );
@@ -105,44 +159,200 @@ pub fn generate_templates(input: TokenStream) -> TokenStream {
             }
         };
 
-        let doc = match read_docx(&buf) {
-            Ok(doc) => doc,
-            Err(_) => {
-                print_docxide_message("Unable to read docx content. Skipping.", &path);
-                continue;
-            }
+        let naming = args.naming_for(&type_name);
+        let Some(mut content) = template_content(&buf, &args.delimiters, &naming) else {
+            print_docxide_message("Unable to read docx content. Skipping.", &path);
+            continue;
         };
-
-        let mut corpus = collect_text_from_document_children(doc.document.children);
-
-        let section = &doc.document.section_property;
-        for (_, header) in section.get_headers() {
-            corpus.extend(collect_text_from_header_children(&header.children));
+        templates.insert(type_name.clone());
+        for name in &content.renamed {
+            let scoped = args.template_renames.get(&type_name).is_some_and(|renames| renames.contains_key(name));
+            used_renames.insert((scoped.then(|| type_name.clone()), name.clone()));
         }
-        for (_, footer) in section.get_footers() {
-            corpus.extend(collect_text_from_footer_children(&footer.children));
+        let collisions: Vec<String> = content.collisions.iter().map(Collision::describe).collect();
+        if args.naming.collisions == Collisions::Merge && !collisions.is_empty() {
+            print_docxide_message(
+                &format!(
+                    "Placeholder names spelled differently are merged into one field:\n{}\n\
+                    Set `collisions = error` or `collisions = suffix` to keep them apart, or map them with `rename`.",
+                    collisions.join("\n")
+                ),
+                &path,
+            );
+        }
+        if args.naming.collisions == Collisions::Error && !collisions.is_empty() {
+            panic!(
+                "\n\n[Docxide-template] Placeholder names in {:?} collide:\n{}\n\
+                Use one spelling per field, map them with `rename`, or set `collisions = merge` or `collisions = suffix`.\n",
+                path,
+                collisions.join("\n")
+            );
+        }
+        let declared_order = read_xml_part(&buf, "docProps/custom.xml")
+            .and_then(|xml| custom_property(&xml, FIELD_ORDER_PROPERTY));
+        if let Err(message) = content.order_fields(args.order, declared_order.as_deref(), &naming) {
+            panic!("\n\n[Docxide-template] Field order of {:?}: {}.\n", path, message);
         }
 
-        let content = generate_struct_content(corpus);
+        for (field, setter) in content.fields.iter().zip(builder_setters(&content.fields)) {
+            if *field != setter {
+                print_docxide_message(
+                    &format!("The builder sets the field `{}` with `{}()`, as `build()` returns the struct.", field, setter),
+                    &path,
+                );
+            }
+        }
+
+        // Dropdown enums and builders are named after the struct, so `Order.docx` with a
+        // `status` dropdown and `OrderStatus.docx` would both declare `OrderStatus`
+        for name in companion_type_names(&type_ident, &content) {
+            if let Some(existing_path) = seen_type_names.get(&name) {
+                panic!(
+                    "\n\n[Docxide-template] Type name collision: both {:?} and {:?} produce the type `{}`.\n\
+                    Rename one of the files or the placeholder to avoid this conflict.\n",
+                    existing_path, path, name
+                );
+            }
+            seen_type_names.insert(name, path.clone());
+        }
 
         let abs_path = path.canonicalize().expect("Failed to canonicalize template path");
         let abs_path_str = abs_path.to_str().expect("Failed to convert path to string");
 
+        let precomputed = if embed { precompute(&buf, content.replacement_patterns()) } else { Precomputed::default() };
+
         let template_struct = generate_struct(
             type_ident,
             abs_path_str,
-            &content.fields,
-            &content.replacement_placeholders,
-            &content.replacement_fields,
+            &content,
+            &args,
             embed,
+            serde,
+            &precomputed,
         );
 
         structs.push(template_struct)
     }
 
+    report_unused_renames(&args, &used_renames, &templates);
+
     let combined = quote! {
         #(#structs)*
     };
 
     combined.into()
 }
+
+/// Reports `rename` keys that match no placeholder, which are most likely
+/// misspelled, and templates named in `rename` that aren't in the directory.
+fn report_unused_renames(
+    args: &TemplateArgs,
+    used: &HashSet<(Option<String>, String)>,
+    templates: &HashSet<String>,
+) {
+    let folder = PathBuf::from(args.path.value());
+    let mut unused: Vec<String> = args
+        .naming
+        .renames
+        .keys()
+        .filter(|name| !used.contains(&(None, name.to_string())))
+        .map(|name| format!("`rename` key {:?} matches no placeholder in any template.", name))
+        .collect();
+    for (template, renames) in &args.template_renames {
+        if !templates.contains(template) {
+            unused.push(format!("`rename` lists `{}`, which no template in the directory generates.", template));
+            continue;
+        }
+        unused.extend(
+            renames
+                .keys()
+                .filter(|name| !used.contains(&(Some(template.clone()), name.to_string())))
+                .map(|name| format!("`rename` key {:?} for `{}` matches none of its placeholders.", name, template)),
+        );
+    }
+    unused.sort();
+    for message in unused {
+        print_docxide_message(&message, &folder);
+    }
+}
+
+/// Implements rendering for a struct written by hand, checking at compile time
+/// that its fields and the template's placeholders match exactly.
+///
+/// ```rust,ignore
+/// use docxide_template::DocxTemplate;
+///
+/// /// An invoice sent to a customer.
+/// #[derive(DocxTemplate)]
+/// #[docx(path = "templates/Invoice.docx")]
+/// pub struct Invoice {
+///     pub customer_name: String,
+///     /// Filled into `{Total}` rather than `{amount}`.
+///     #[docx(rename = "Total")]
+///     pub amount: f64,
+/// }
+/// ```
+///
+/// The template path is resolved the same way as for `generate_templates!`, and
+/// `delimiters = ("{{", "}}")` is accepted alongside it. Fields are matched to
+/// placeholders by their snake_case names, or by the placeholder name given with
+/// `#[docx(rename = "...")]`. A field without a placeholder, or a placeholder
+/// without a field, fails to compile.
+///
+/// Text and combo box fields may be of any type implementing `Display`. Dropdown
+/// fields must implement `docxide_template::DropdownValue`, as the enums
+/// `generate_templates!` generates for dropdowns do, with no entry missing from
+/// the template's list. Date picker fields must be `docxide_template::Date` and
+/// chart series fields `Vec<(String, f64)>`. The struct gets the same `save`, `to_bytes` and
+/// `write_to` methods as generated structs.
+#[proc_macro_derive(DocxTemplate, attributes(docx))]
+pub fn derive_docx_template(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    derive::derive_docx_template(&input, cfg!(feature = "embed"))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Finds the placeholders of the template in `buf`, or `None` if it has no
+/// readable document part.
+fn template_content(buf: &[u8], delimiters: &Delimiters, naming: &FieldNaming) -> Option<StructContent> {
+    let document_xml = read_xml_part(buf, DOCUMENT_PART)?;
+    let (final_section_parts, other_section_parts) = read_header_footer_parts(buf, &document_xml);
+    let header_footer_parts: Vec<_> = final_section_parts.iter().chain(&other_section_parts).collect();
+
+    // Fields are discovered in the body, then the final section's headers and
+    // footers, as they always were; text boxes, notes and the other sections'
+    // headers and footers come after, so existing templates keep their order.
+    let (body_texts, text_box_texts) = collect_paragraph_texts_by_nesting(&document_xml);
+    let mut corpus: Vec<SourceText> = at(DOCUMENT_PART, body_texts).collect();
+    for (name, xml) in &final_section_parts {
+        corpus.extend(at(name, collect_paragraph_texts(xml)));
+    }
+    corpus.extend(at(DOCUMENT_PART, text_box_texts));
+    for name in NOTE_PARTS {
+        if let Some(xml) = read_xml_part(buf, name) {
+            corpus.extend(at(name, collect_paragraph_texts(&xml)));
+        }
+    }
+    for (name, xml) in &other_section_parts {
+        corpus.extend(at(name, collect_paragraph_texts(xml)));
+    }
+    let parts = std::iter::once((DOCUMENT_PART, &document_xml))
+        .chain(header_footer_parts.iter().map(|(name, xml)| (name.as_str(), xml)));
+    for (name, xml) in parts.clone() {
+        corpus.extend(at(name, collect_field_and_alt_texts(xml)));
+    }
+    corpus.extend(at("a relationship target", collect_external_targets(buf)));
+    corpus.extend(at("the document properties", collect_text_from_properties(buf)));
+
+    let controls: Vec<_> = parts.flat_map(|(_, xml)| collect_content_controls(xml)).collect();
+
+    let series_names = collect_chart_series_names(buf);
+
+    Some(generate_struct_content(corpus, &series_names, &controls, delimiters, naming))
+}
+
+/// `texts` found at `location`.
+fn at(location: &str, texts: Vec<String>) -> impl Iterator<Item = SourceText> + '_ {
+    texts.into_iter().map(move |text| SourceText { location: location.to_string(), text })
+}
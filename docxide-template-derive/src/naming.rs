@@ -1,11 +1,92 @@
 use heck::{AsSnakeCase, ToPascalCase};
+use proc_macro2::{Ident, Span};
+use std::collections::HashMap;
 use std::path::Path;
 
+/// How placeholder names become field names, set with the `rename`,
+/// `keyword_suffix` and `collisions` options.
+#[derive(Clone, Default)]
+pub struct FieldNaming {
+    /// Field names given to placeholder names.
+    pub renames: HashMap<String, String>,
+    /// Appended to field names that are Rust keywords, which are otherwise
+    /// made raw identifiers like `r#type`.
+    pub keyword_suffix: Option<String>,
+    pub collisions: Collisions,
+}
+
+/// What to do with placeholder names spelled differently, like `FirstName` and
+/// `first_name`, that make the same field name. Renamed placeholders never collide.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Collisions {
+    /// Fill them all from one field.
+    #[default]
+    Merge,
+    /// Fail to compile, listing the spellings and where they're used.
+    Error,
+    /// Give each spelling after the first a field of its own: `first_name_2`, ...
+    Suffix,
+}
+
+impl FieldNaming {
+    /// The field filled by the placeholder `name`, or `None` if its name can't
+    /// be made a valid identifier.
+    pub fn field_ident(&self, name: &str) -> Option<Ident> {
+        if let Some(field) = self.renames.get(name) {
+            return syn::parse_str(field).ok();
+        }
+        let field_name = placeholder_to_field_name(name);
+        if let Ok(ident) = syn::parse_str(&field_name) {
+            return Some(ident);
+        }
+        // Keywords are the only names a suffix makes valid
+        let is_keyword = field_name.chars().any(char::is_alphabetic)
+            && syn::parse_str::<Ident>(&format!("{}_", field_name)).is_ok();
+        if !is_keyword {
+            return None;
+        }
+        match &self.keyword_suffix {
+            Some(suffix) => syn::parse_str(&format!("{}{}", field_name, suffix)).ok(),
+            // These can't be raw identifiers
+            None if matches!(field_name.as_str(), "self" | "super" | "crate") => {
+                syn::parse_str(&format!("{}_", field_name)).ok()
+            }
+            None => Some(Ident::new_raw(&field_name, Span::call_site())),
+        }
+    }
+}
+
 pub fn placeholder_to_field_name(variable: &str) -> String {
-    let sanitized = variable.replace(' ', "_").replace(':', "_");
+    let sanitized = variable.replace([' ', ':'], "_");
     format!("{}", AsSnakeCase(sanitized))
 }
 
+/// Enum variant name for a dropdown or combo box list item.
+pub fn list_item_to_variant_name(display_text: &str) -> String {
+    let name = display_text.to_pascal_case();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("Item{}", name)
+    } else {
+        name
+    }
+}
+
+pub fn derive_type_name_from_filename(filename: &Path) -> Result<String, String> {
+    let file_stem = filename
+        .file_stem()
+        .ok_or_else(|| "Could not extract file stem".to_owned())?
+        .to_str()
+        .ok_or_else(|| "Could not convert file stem to string".to_owned())?;
+
+    let type_name = file_stem.to_pascal_case();
+
+    if syn::parse_str::<syn::Ident>(&type_name).is_err() {
+        return Err("Invalid type name derived from filename".to_owned());
+    }
+
+    Ok(type_name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,6 +109,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn renamed_placeholders_keep_chosen_field_name() {
+        let naming = FieldNaming {
+            renames: HashMap::from([("ZIPCODE".to_string(), "zip_code".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(naming.field_ident("ZIPCODE").unwrap(), "zip_code");
+        assert_eq!(naming.field_ident("FirstName").unwrap(), "first_name");
+    }
+
+    #[test]
+    fn keyword_placeholders_become_raw_or_suffixed() {
+        let raw = FieldNaming::default();
+        assert_eq!(raw.field_ident("type").unwrap(), "r#type");
+        assert_eq!(raw.field_ident("Match").unwrap(), "r#match");
+        assert_eq!(raw.field_ident("async").unwrap(), "r#async");
+        // `Self` is snake-cased to `self` before the check
+        assert_eq!(raw.field_ident("Self").unwrap(), "self_");
+        assert_eq!(raw.field_ident("super").unwrap(), "super_");
+        assert_eq!(raw.field_ident("1st").map(|i| i.to_string()), None);
+
+        let suffixed = FieldNaming { keyword_suffix: Some("_field".to_string()), ..Default::default() };
+        assert_eq!(suffixed.field_ident("type").unwrap(), "type_field");
+        assert_eq!(suffixed.field_ident("name").unwrap(), "name");
+    }
+
     #[test]
     fn whitespace_variants_produce_same_field_name() {
         let variants = vec![
@@ -52,6 +159,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn list_item_variant_names() {
+        let cases = vec![
+            ("Draft", "Draft"),
+            ("Not started", "NotStarted"),
+            ("in-progress", "InProgress"),
+            ("1 day", "Item1Day"),
+            ("", ""),
+        ];
+        for (input, expected) in cases {
+            let result = list_item_to_variant_name(input);
+            assert_eq!(result, expected, "list_item_to_variant_name({:?})", input);
+        }
+    }
+
     #[test]
     fn type_name_from_various_filenames() {
         let cases = vec![
@@ -78,19 +200,3 @@ mod tests {
         assert!(result.is_err() || syn::parse_str::<syn::Ident>(&result.unwrap()).is_err());
     }
 }
-
-pub fn derive_type_name_from_filename(filename: &Path) -> Result<String, String> {
-    let file_stem = filename
-        .file_stem()
-        .ok_or_else(|| "Could not extract file stem".to_owned())?
-        .to_str()
-        .ok_or_else(|| "Could not convert file stem to string".to_owned())?;
-
-    let type_name = file_stem.to_pascal_case();
-
-    if syn::parse_str::<syn::Ident>(&type_name).is_err() {
-        return Err("Invalid type name derived from filename".to_owned());
-    }
-
-    Ok(type_name)
-}
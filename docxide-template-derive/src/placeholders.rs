@@ -1,44 +1,382 @@
+use std::collections::HashMap;
+
+use quote::format_ident;
 use regex::Regex;
+use syn::ext::IdentExt;
 use syn::LitStr;
 
-use crate::naming::placeholder_to_field_name;
+use crate::content_controls::{ContentControl, ControlKind};
+use crate::naming::{list_item_to_variant_name, Collisions, FieldNaming};
+
+pub(crate) struct ChoiceVariant {
+    pub(crate) ident: proc_macro2::Ident,
+    pub(crate) display_text: String,
+}
+
+pub(crate) enum FieldType {
+    Text,
+    /// Backed by a dropdown (`open: false`) or combo box (`open: true`) content control.
+    Choice { variants: Vec<ChoiceVariant>, open: bool },
+    /// Backed by a date picker content control.
+    Date { format: String },
+    /// Named in a chart series, filled with `(category, value)` points.
+    ChartSeries,
+}
 
 pub(crate) struct StructContent {
     pub(crate) fields: Vec<proc_macro2::Ident>,
+    /// Type of each field, in the same order as `fields`.
+    pub(crate) field_types: Vec<FieldType>,
+    /// Names of each field as spelled in the template's placeholders, where
+    /// they differ from the field name, in the same order as `fields`.
+    pub(crate) aliases: Vec<Vec<String>>,
     /// All placeholder/field pairs for replacements (may have multiple
     /// placeholder strings mapping to the same field, e.g. `{name}` and `{ name }`).
     pub(crate) replacement_placeholders: Vec<LitStr>,
     pub(crate) replacement_fields: Vec<proc_macro2::Ident>,
+    /// Escape sequences found in the template and the literal text they render as.
+    pub(crate) escapes: Vec<(LitStr, LitStr)>,
+    /// Placeholder/field pairs for chart series, like `replacement_placeholders`.
+    pub(crate) chart_placeholders: Vec<LitStr>,
+    pub(crate) chart_fields: Vec<proc_macro2::Ident>,
+    /// Fields filled by placeholder names spelled in more than one way.
+    pub(crate) collisions: Vec<Collision>,
+    /// Placeholder names given their field with `rename`.
+    pub(crate) renamed: Vec<String>,
+}
+
+/// Text that may hold placeholders, and where in the template it's from.
+pub(crate) struct SourceText {
+    /// The part, like `word/document.xml`, or what else the text is.
+    pub(crate) location: String,
+    pub(crate) text: String,
+}
+
+/// Placeholder names spelled differently that make the same field name.
+pub(crate) struct Collision {
+    pub(crate) field: proc_macro2::Ident,
+    /// Each spelling, as first written, and where it's used.
+    pub(crate) spellings: Vec<(String, Vec<String>)>,
+}
+
+impl Collision {
+    /// The field and every spelling with its locations, one per line.
+    pub(crate) fn describe(&self) -> String {
+        let mut lines = vec![format!("  `{}` is filled by:", self.field)];
+        for (placeholder, locations) in &self.spellings {
+            lines.push(format!("    {} in {}", placeholder, locations.join(", ")));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Assigns placeholder names to fields as they're found, keeping track of the
+/// spellings that end up in each field.
+struct FieldAssigner<'a> {
+    naming: &'a FieldNaming,
+    /// The field of each placeholder name seen so far.
+    assigned: HashMap<String, proc_macro2::Ident>,
+    /// Spellings made into each field name without being renamed into it.
+    spellings: Vec<(proc_macro2::Ident, Vec<Spelling>)>,
+}
+
+struct Spelling {
+    name: String,
+    placeholder: String,
+    locations: Vec<String>,
+}
+
+impl<'a> FieldAssigner<'a> {
+    fn new(naming: &'a FieldNaming) -> Self {
+        Self { naming, assigned: HashMap::new(), spellings: Vec::new() }
+    }
+
+    /// The field filled by `placeholder`, whose name is `name`, found at `location`.
+    fn assign(&mut self, name: &str, placeholder: &str, location: &str) -> Option<proc_macro2::Ident> {
+        let ident = match self.assigned.get(name) {
+            Some(ident) => ident.clone(),
+            None => {
+                let mut ident = self.naming.field_ident(name)?;
+                if self.naming.collisions == Collisions::Suffix && !self.naming.renames.contains_key(name) {
+                    // Taken by another spelling, suffixed or not, like a
+                    // `{first_name_2}` next to `{FirstName}` and `{first-name}`
+                    let taken = |candidate: &proc_macro2::Ident| {
+                        self.assigned
+                            .iter()
+                            .any(|(other, field)| field == candidate && !self.naming.renames.contains_key(other))
+                    };
+                    if taken(&ident) {
+                        let base = ident.unraw();
+                        let mut n: usize = 2;
+                        while taken(&format_ident!("{}_{}", base, n)) {
+                            n += 1;
+                        }
+                        ident = format_ident!("{}_{}", base, n);
+                    }
+                }
+                self.assigned.insert(name.to_string(), ident.clone());
+                ident
+            }
+        };
+        if !self.naming.renames.contains_key(name) {
+            self.record(&ident, name, placeholder, location);
+        }
+        Some(ident)
+    }
+
+    fn record(&mut self, field: &proc_macro2::Ident, name: &str, placeholder: &str, location: &str) {
+        let spellings = match self.spellings.iter_mut().find(|(f, _)| f == field) {
+            Some((_, spellings)) => spellings,
+            None => {
+                self.spellings.push((field.clone(), Vec::new()));
+                &mut self.spellings.last_mut().unwrap().1
+            }
+        };
+        let spelling = match spellings.iter_mut().find(|spelling| spelling.name == name) {
+            Some(spelling) => spelling,
+            None => {
+                spellings.push(Spelling { name: name.to_string(), placeholder: placeholder.to_string(), locations: Vec::new() });
+                spellings.last_mut().unwrap()
+            }
+        };
+        if !spelling.locations.iter().any(|l| l == location) {
+            spelling.locations.push(location.to_string());
+        }
+    }
+
+    fn collisions(self) -> Vec<Collision> {
+        self.spellings
+            .into_iter()
+            .filter(|(_, spellings)| spellings.len() > 1)
+            .map(|(field, spellings)| Collision {
+                field,
+                spellings: spellings.into_iter().map(|s| (s.placeholder, s.locations)).collect(),
+            })
+            .collect()
+    }
+}
+
+/// Where `text` is found in the template, for diagnostics.
+fn describe_location(source: &SourceText) -> String {
+    const MAX_CHARS: usize = 40;
+    let text = source.text.trim();
+    let excerpt = match text.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    };
+    format!("{} (\"{}\")", source.location, excerpt)
+}
+
+/// Custom document property in which a template declares its field order.
+pub(crate) const FIELD_ORDER_PROPERTY: &str = "DocxideFieldOrder";
+
+/// Order of the fields of generated structs, and so of `new()`'s parameters.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum FieldOrder {
+    /// In order of first appearance in the template.
+    Document,
+    Alphabetical,
+    /// As listed in the template's `DocxideFieldOrder` property, required on every template.
+    Declared,
+}
+
+impl StructContent {
+    /// The placeholders and escape sequences replaced in the template, in the
+    /// order the generated `replacements()` lists them.
+    pub(crate) fn replacement_patterns(&self) -> Vec<String> {
+        let escapes = self.escapes.iter().map(|(escape, _)| escape);
+        self.replacement_placeholders.iter().chain(escapes).map(LitStr::value).collect()
+    }
+
+    /// Reorders the fields by `order`, or as `declared` in the template's
+    /// field order property if it has one. A declared order must list every
+    /// field exactly once, so adding or renaming a placeholder without
+    /// updating it fails to compile instead of shifting parameters.
+    pub(crate) fn order_fields(
+        &mut self,
+        order: FieldOrder,
+        declared: Option<&str>,
+        naming: &FieldNaming,
+    ) -> Result<(), String> {
+        let mut entries: Vec<_> = std::mem::take(&mut self.fields)
+            .into_iter()
+            .zip(std::mem::take(&mut self.field_types))
+            .zip(std::mem::take(&mut self.aliases))
+            .collect();
+
+        match declared {
+            Some(declared) => {
+                let mut names: Vec<proc_macro2::Ident> = Vec::new();
+                for name in declared.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                    match naming.field_ident(name) {
+                        Some(ident) => names.push(ident),
+                        None => return Err(format!("{} lists `{}`, which is not a field", FIELD_ORDER_PROPERTY, name)),
+                    }
+                }
+                for (i, name) in names.iter().enumerate() {
+                    if names[..i].contains(name) {
+                        return Err(format!("{} lists `{}` twice", FIELD_ORDER_PROPERTY, name));
+                    }
+                    if !entries.iter().any(|((field, _), _)| field == name) {
+                        return Err(format!("{} lists `{}`, which is not a field", FIELD_ORDER_PROPERTY, name));
+                    }
+                }
+                if let Some(((field, _), _)) = entries.iter().find(|((field, _), _)| !names.iter().any(|n| field == n)) {
+                    return Err(format!("{} does not list the field `{}`", FIELD_ORDER_PROPERTY, field));
+                }
+                entries.sort_by_key(|((field, _), _)| names.iter().position(|n| field == n));
+            }
+            None if order == FieldOrder::Declared => {
+                return Err(format!("the template has no {} custom property", FIELD_ORDER_PROPERTY));
+            }
+            None if order == FieldOrder::Alphabetical => {
+                entries.sort_by_key(|((field, _), _)| field.unraw().to_string());
+            }
+            None => {}
+        }
+
+        for ((field, field_type), aliases) in entries {
+            self.fields.push(field);
+            self.field_types.push(field_type);
+            self.aliases.push(aliases);
+        }
+        Ok(())
+    }
+}
+
+/// The opening and closing markers around a placeholder name, `{` and `}` by default.
+pub(crate) struct Delimiters {
+    pub(crate) open: String,
+    pub(crate) close: String,
 }
 
-pub(crate) fn generate_struct_content(corpus: Vec<String>) -> StructContent {
-    let re = Regex::new(r"(\{\s*[^}]+\s*\})").unwrap();
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self::new("{", "}")
+    }
+}
+
+impl Delimiters {
+    pub(crate) fn new(open: impl Into<String>, close: impl Into<String>) -> Self {
+        Self { open: open.into(), close: close.into() }
+    }
+
+    /// A backslash followed by a delimiter renders the delimiter literally.
+    pub(crate) fn escapes(&self) -> [(String, String); 2] {
+        [
+            (format!("\\{}", self.open), self.open.clone()),
+            (format!("\\{}", self.close), self.close.clone()),
+        ]
+    }
+
+    /// Regex matching either an escaped delimiter or a whole placeholder,
+    /// delimiters included. Only placeholders are captured, in group 1.
+    pub(crate) fn regex(&self) -> Regex {
+        let mut close_chars = self.close.chars();
+        let name = match (close_chars.next(), close_chars.next()) {
+            (Some(c), None) => format!("[^{}]+", regex::escape(&c.to_string())),
+            _ => ".+?".to_string(),
+        };
+        Regex::new(&format!(
+            r"\\(?:{open}|{close})|({open}\s*{name}\s*{close})",
+            open = regex::escape(&self.open),
+            close = regex::escape(&self.close),
+            name = name,
+        ))
+        .unwrap()
+    }
+
+    /// The name inside a placeholder, without delimiters and surrounding whitespace.
+    fn name<'a>(&self, placeholder: &'a str) -> &'a str {
+        let inner = placeholder
+            .strip_prefix(self.open.as_str())
+            .and_then(|p| p.strip_suffix(self.close.as_str()))
+            .unwrap_or(placeholder);
+        inner.trim()
+    }
+}
+
+fn choice_variants(control_kind: &ControlKind) -> Option<(Vec<ChoiceVariant>, bool)> {
+    let (items, open) = match control_kind {
+        ControlKind::DropDown(items) => (items, false),
+        ControlKind::ComboBox(items) => (items, true),
+        ControlKind::Date { .. } => return None,
+    };
+    let span = proc_macro::Span::call_site().into();
+    let mut seen = std::collections::HashSet::new();
+    let mut variants = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let mut name = list_item_to_variant_name(&item.display_text);
+        if syn::parse_str::<syn::Ident>(&name).is_err() || (open && name == "Other") || seen.contains(&name) {
+            name = format!("Item{}", i + 1);
+        }
+        seen.insert(name.clone());
+        variants.push(ChoiceVariant {
+            ident: syn::Ident::new(&name, span),
+            display_text: item.display_text.clone(),
+        });
+    }
+    Some((variants, open))
+}
+
+fn field_type_for_control(control: &ContentControl) -> FieldType {
+    match &control.kind {
+        ControlKind::Date { format } => FieldType::Date { format: format.clone() },
+        kind => match choice_variants(kind) {
+            Some((variants, open)) if !variants.is_empty() => FieldType::Choice { variants, open },
+            _ => FieldType::Text,
+        },
+    }
+}
+
+/// Records `name` as a spelling of `field`, unless it is the name of a field,
+/// as `first_name` is when `collisions = suffix` gives it to `first_name_2`.
+fn add_alias(aliases: &mut [Vec<String>], fields: &[proc_macro2::Ident], field: &proc_macro2::Ident, name: &str) {
+    let Some(index) = fields.iter().position(|f| f == field) else {
+        return;
+    };
+    if !fields.iter().any(|f| f.unraw() == name) && !aliases[index].iter().any(|alias| alias == name) {
+        aliases[index].push(name.to_string());
+    }
+}
+
+pub(crate) fn generate_struct_content(
+    corpus: Vec<SourceText>,
+    series_names: &[String],
+    controls: &[ContentControl],
+    delimiters: &Delimiters,
+    naming: &FieldNaming,
+) -> StructContent {
+    let re = delimiters.regex();
     let mut seen_fields = std::collections::HashSet::new();
     let mut seen_placeholders = std::collections::HashSet::new();
     let mut fields = Vec::new();
+    let mut aliases: Vec<Vec<String>> = Vec::new();
     let mut replacement_placeholders = Vec::new();
     let mut replacement_fields = Vec::new();
+    let mut assigner = FieldAssigner::new(naming);
     let span = proc_macro::Span::call_site().into();
 
-    for text in &corpus {
-        for cap in re.captures_iter(text) {
-            let placeholder = cap[1].to_string();
-            let cleaned =
-                placeholder.trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace());
-            let field_name = placeholder_to_field_name(cleaned);
-
-            if syn::parse_str::<syn::Ident>(&field_name).is_err() {
+    for source in &corpus {
+        for cap in re.captures_iter(&source.text) {
+            let Some(placeholder) = cap.get(1) else {
+                continue;
+            };
+            let placeholder = placeholder.as_str().to_string();
+            let location = describe_location(source);
+            let Some(ident) = assigner.assign(delimiters.name(&placeholder), &placeholder, &location) else {
                 println!(
                     "\x1b[34m[Docxide-template]\x1b[0m Invalid placeholder name in file: {}",
                     placeholder
                 );
                 continue;
-            }
+            };
 
-            let ident = syn::Ident::new(&field_name, span);
-            if seen_fields.insert(field_name) {
+            if seen_fields.insert(ident.to_string()) {
                 fields.push(ident.clone());
+                aliases.push(Vec::new());
             }
+            add_alias(&mut aliases, &fields, &ident, delimiters.name(&placeholder));
             if seen_placeholders.insert(placeholder.clone()) {
                 replacement_placeholders.push(syn::LitStr::new(&placeholder, span));
                 replacement_fields.push(ident);
@@ -46,9 +384,91 @@ pub(crate) fn generate_struct_content(corpus: Vec<String>) -> StructContent {
         }
     }
 
+    // A placeholder inside a dropdown, combo box or date picker takes its type
+    // from the control. The first control a field appears in wins.
+    let mut field_types: Vec<FieldType> = fields.iter().map(|_| FieldType::Text).collect();
+    let mut typed_fields = std::collections::HashSet::new();
+    for control in controls {
+        for placeholder in re.captures_iter(&control.text).filter_map(|cap| cap.get(1)) {
+            let Some(ident) = assigner.assigned.get(delimiters.name(placeholder.as_str())).cloned() else {
+                continue;
+            };
+            let Some(index) = fields.iter().position(|f| *f == ident) else {
+                continue;
+            };
+            if typed_fields.insert(ident.to_string()) {
+                field_types[index] = field_type_for_control(control);
+            }
+        }
+    }
+
+    // A placeholder in a chart series name binds the series to a field of its own.
+    let mut chart_placeholders = Vec::new();
+    let mut chart_fields = Vec::new();
+    for name in series_names {
+        for placeholder in re.captures_iter(name).filter_map(|cap| cap.get(1)) {
+            let placeholder = placeholder.as_str();
+            let Some(ident) = assigner.assign(delimiters.name(placeholder), placeholder, "a chart series name") else {
+                println!(
+                    "\x1b[34m[Docxide-template]\x1b[0m Invalid placeholder name in chart series: {}",
+                    placeholder
+                );
+                continue;
+            };
+            if replacement_fields.contains(&ident) {
+                println!(
+                    "\x1b[34m[Docxide-template]\x1b[0m Placeholder {} is used both as text and as a chart series name, the chart series is left unfilled.",
+                    placeholder
+                );
+                continue;
+            }
+
+            if seen_fields.insert(ident.to_string()) {
+                fields.push(ident.clone());
+                field_types.push(FieldType::ChartSeries);
+                aliases.push(Vec::new());
+            }
+            add_alias(&mut aliases, &fields, &ident, delimiters.name(placeholder));
+            if seen_placeholders.insert(placeholder.to_string()) {
+                chart_placeholders.push(syn::LitStr::new(placeholder, span));
+                chart_fields.push(ident);
+            }
+        }
+    }
+
+    let escapes = delimiters
+        .escapes()
+        .into_iter()
+        .filter(|(escape, _)| corpus.iter().any(|source| source.text.contains(escape.as_str())))
+        .map(|(escape, literal)| (LitStr::new(&escape, span), LitStr::new(&literal, span)))
+        .collect();
+
     StructContent {
         fields,
+        field_types,
+        aliases,
         replacement_placeholders,
         replacement_fields,
+        escapes,
+        chart_placeholders,
+        chart_fields,
+        renamed: assigner.assigned.keys().filter(|name| naming.renames.contains_key(*name)).cloned().collect(),
+        collisions: assigner.collisions(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suffixes_skip_fields_taken_by_other_placeholders() {
+        let naming = FieldNaming { collisions: Collisions::Suffix, ..FieldNaming::default() };
+        let mut assigner = FieldAssigner::new(&naming);
+        let fields: Vec<String> = ["FirstName", "first_name_2", "first-name", "first_name", "FirstName"]
+            .iter()
+            .map(|name| assigner.assign(name, name, "word/document.xml").unwrap().to_string())
+            .collect();
+        assert_eq!(fields, ["first_name", "first_name_2", "first_name_3", "first_name_4", "first_name"]);
     }
 }
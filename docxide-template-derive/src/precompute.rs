@@ -0,0 +1,106 @@
+//! Locating template text and placeholders at compile time, for embed mode.
+//!
+//! The text is located and matched with the runtime's own functions from
+//! `docxide-template-core`; the generated code hands the results to the
+//! runtime, which splices values in without scanning the parts again.
+
+use std::io::{Cursor, Read};
+
+use docxide_template_core::{locate_text, span_edits, TextSpan};
+
+/// The text elements of a part, in document order, and the placeholders in them.
+pub(crate) struct LocatedPart {
+    pub(crate) name: String,
+    /// Spans with the index of their element in `TEXT_ELEMENTS`.
+    pub(crate) spans: Vec<(u8, TextSpan)>,
+    /// Edits as `(span, from, to, placeholder)`, see `PrecomputedEdit`.
+    pub(crate) edits: Vec<(usize, usize, usize, Option<usize>)>,
+}
+
+/// The located parts of a template and the placeholders their edits refer to.
+#[derive(Default)]
+pub(crate) struct Precomputed {
+    /// Placeholders and escapes, in the order the generated `replacements()` lists them.
+    pub(crate) placeholders: Vec<String>,
+    pub(crate) parts: Vec<LocatedPart>,
+}
+
+/// Whether the runtime treats `name` as a WordprocessingML part to locate text in.
+fn is_document_part(name: &str) -> bool {
+    let chart = name.strip_prefix("word/charts/chart").is_some_and(|rest| !rest.contains('/'));
+    name.ends_with(".xml") && !name.starts_with("docProps/") && name != "[Content_Types].xml" && !chart
+}
+
+/// Locates the text of every WordprocessingML part of a `.docx`, and the
+/// matches of `placeholders` in it.
+pub(crate) fn precompute(buf: &[u8], placeholders: Vec<String>) -> Precomputed {
+    let Ok(mut archive) = zip::ZipArchive::new(Cursor::new(buf)) else {
+        return Precomputed::default();
+    };
+    let names: Vec<String> = archive.file_names().filter(|n| is_document_part(n)).map(str::to_string).collect();
+    let replacements: Vec<(&str, usize)> = placeholders.iter().enumerate().map(|(i, p)| (p.as_str(), i)).collect();
+
+    let mut parts = Vec::new();
+    for name in names {
+        let Ok(mut file) = archive.by_name(&name) else {
+            continue;
+        };
+        let mut xml = String::new();
+        if file.read_to_string(&mut xml).is_err() {
+            continue;
+        }
+        parts.push(locate_part(name, &xml, &replacements));
+    }
+    Precomputed { placeholders, parts }
+}
+
+fn locate_part(name: String, xml: &str, replacements: &[(&str, usize)]) -> LocatedPart {
+    let streams = locate_text(xml);
+    let mut edits = Vec::new();
+    for spans in &streams {
+        for (index, span_edits) in span_edits(spans, replacements) {
+            for (from, to, placeholder) in span_edits {
+                edits.push((spans[index].start, from, to, placeholder));
+            }
+        }
+    }
+
+    let mut spans: Vec<(u8, TextSpan)> = streams
+        .into_iter()
+        .enumerate()
+        .flat_map(|(kind, spans)| spans.into_iter().map(move |span| (kind as u8, span)))
+        .collect();
+    spans.sort_by_key(|(_, span)| span.start);
+    // Refer to spans by their index in document order
+    let edits = edits
+        .into_iter()
+        .map(|(start, from, to, placeholder)| {
+            let span = spans.partition_point(|(_, span)| span.start < start);
+            (span, from, to, placeholder)
+        })
+        .collect();
+    LocatedPart { name, spans, edits }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_spans_and_edits_in_document_order() {
+        let xml = r#"<w:p><w:r><w:t>{Na</w:t></w:r><w:r><w:instrText>{x}</w:instrText><w:t>me} &amp;</w:t></w:r></w:p>"#;
+        let part = locate_part("word/document.xml".into(), xml, &[("{Name}", 0), ("{x}", 1)]);
+        let found: Vec<_> = part.spans.iter().map(|(kind, s)| (*kind, &xml[s.start..s.end], s.text.as_str())).collect();
+        assert_eq!(found, vec![(0, "{Na", "{Na"), (1, "{x}", "{x}"), (0, "me} &amp;", "me} &")]);
+        assert_eq!(part.edits, vec![(0, 0, 3, Some(0)), (2, 0, 3, None), (1, 0, 3, Some(1))]);
+    }
+
+    #[test]
+    fn document_parts() {
+        assert!(is_document_part("word/document.xml"));
+        assert!(is_document_part("word/charts/colors1.xml"));
+        assert!(!is_document_part("word/charts/chart1.xml"));
+        assert!(!is_document_part("docProps/core.xml"));
+        assert!(!is_document_part("word/_rels/document.xml.rels"));
+    }
+}
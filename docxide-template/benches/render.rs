@@ -0,0 +1,91 @@
+//! Rendering time against document size.
+//!
+//! Builds documents of doubling size, each paragraph holding a placeholder split
+//! across runs, and times `build_docx_bytes` on them. With a linear engine the
+//! time per KB of document XML stays flat as documents grow; the bench fails if
+//! it grows more than [`MAX_SLOWDOWN`] times from the smallest document to the
+//! largest, which a quadratic engine, at 32 times the time per KB, can't pass.
+//!
+//! ```bash
+//! cargo bench -p docxide-template
+//! ```
+
+use std::io::{Cursor, Write};
+use std::time::{Duration, Instant};
+
+use docxide_template::__private::build_docx_bytes;
+
+const SIZES: [usize; 6] = [500, 1_000, 2_000, 4_000, 8_000, 16_000];
+const RUNS: u32 = 5;
+/// How much the time per KB may grow across `SIZES` before the bench fails.
+const MAX_SLOWDOWN: f64 = 3.0;
+
+fn document_xml(paragraphs: usize) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>"#,
+    ));
+    for i in 0..paragraphs {
+        xml.push_str(&format!(
+            concat!(
+                r#"<w:p><w:r><w:t xml:space="preserve">Item {} for {{cust</w:t></w:r>"#,
+                r#"<w:r><w:tab/><w:t>omer}} &amp; {{product}}, see {{url}}.</w:t></w:r></w:p>"#,
+            ),
+            i
+        ));
+    }
+    xml.push_str("</w:body></w:document>");
+    xml
+}
+
+fn docx(paragraphs: usize) -> Vec<u8> {
+    let mut buf = Cursor::new(Vec::new());
+    let mut zip = zip::ZipWriter::new(&mut buf);
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("word/document.xml", options).unwrap();
+    zip.write_all(document_xml(paragraphs).as_bytes()).unwrap();
+    zip.finish().unwrap();
+    buf.into_inner()
+}
+
+fn main() {
+    let replacements = [
+        ("{customer}", "Acme Corporation"),
+        ("{product}", "Widgets <large>"),
+        ("{url}", "https://example.com/?a=1&b=2"),
+    ];
+
+    println!("{:>10} {:>10} {:>12} {:>16}", "paragraphs", "KB", "time", "per KB");
+    let mut per_kb = Vec::new();
+    for paragraphs in SIZES {
+        let template = docx(paragraphs);
+        let kb = document_xml(paragraphs).len() as f64 / 1024.0;
+        build_docx_bytes(&template, &replacements).unwrap();
+
+        let mut best = Duration::MAX;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            build_docx_bytes(&template, &replacements).unwrap();
+            best = best.min(start.elapsed());
+        }
+        per_kb.push(best.as_nanos() as f64 / kb);
+        println!(
+            "{:>10} {:>10.0} {:>9.2} ms {:>10.0} ns ({:.2}x)",
+            paragraphs,
+            kb,
+            best.as_secs_f64() * 1000.0,
+            per_kb[per_kb.len() - 1],
+            per_kb[per_kb.len() - 1] / per_kb[0]
+        );
+    }
+
+    let slowdown = per_kb[per_kb.len() - 1] / per_kb[0];
+    assert!(
+        slowdown <= MAX_SLOWDOWN,
+        "time per KB grew {:.2}x from {} to {} paragraphs, more than the {}x of a linear engine",
+        slowdown,
+        SIZES[0],
+        SIZES[SIZES.len() - 1],
+        MAX_SLOWDOWN
+    );
+}
@@ -0,0 +1,743 @@
+//! Filling of chart series (`word/charts/chartN.xml`) and the workbook embedded
+//! with each chart.
+//!
+//! A series is bound to a field when its name holds the field's placeholder.
+//! Filling rewrites the series' category and value caches, which Word displays,
+//! and the cells they were taken from, which Word shows when editing the data.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Write};
+
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+use crate::prepared::entry_options;
+use crate::properties::attribute;
+use crate::{escape_xml, read_entry, TemplateError};
+
+/// A series placeholder and the `(category, value)` points to fill it with.
+pub(crate) type Series<'a> = (&'a str, &'a [(String, f64)]);
+
+pub(crate) enum CellValue {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+/// A cell to write in a chart's embedded workbook.
+pub(crate) struct CellUpdate {
+    sheet: String,
+    column: u32,
+    row: u32,
+    value: CellValue,
+}
+
+pub(crate) fn is_chart_part(name: &str) -> bool {
+    name.starts_with("word/charts/chart") && name.ends_with(".xml") && !name[17..].contains('/')
+}
+
+/// Fills every series in `xml` whose name holds one of the `series`
+/// placeholders. The placeholder is removed from the series name.
+pub(crate) fn fill_chart(xml: &str, series: &[Series]) -> (String, Vec<CellUpdate>) {
+    let mut updates = Vec::new();
+    let categories = fill_categories(xml, series, &mut updates);
+
+    let mut result = String::with_capacity(xml.len());
+    let mut last = 0;
+    for (start, end) in series_spans(xml) {
+        result.push_str(&xml[last..start]);
+        match fill_series(&xml[start..end], series, &categories, &mut updates) {
+            Some(filled) => result.push_str(&filled),
+            None => result.push_str(&xml[start..end]),
+        }
+        last = end;
+    }
+    result.push_str(&xml[last..]);
+    (result, updates)
+}
+
+/// DrawingML chart namespaces, transitional and strict.
+const CHART_NAMESPACES: [&[u8]; 2] = [
+    b"http://schemas.openxmlformats.org/drawingml/2006/chart",
+    b"http://purl.oclc.org/ooxml/drawingml/chart",
+];
+
+fn is_chart(ns: &ResolveResult) -> bool {
+    match ns {
+        ResolveResult::Bound(Namespace(uri)) => CHART_NAMESPACES.contains(uri),
+        // Fragments without namespace declarations use the conventional prefix
+        ResolveResult::Unknown(prefix) => prefix == b"c",
+        ResolveResult::Unbound => false,
+    }
+}
+
+/// Byte ranges of the `c:ser` elements in a chart, whatever their prefix.
+fn series_spans(xml: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut reader = NsReader::from_str(xml);
+    reader.config_mut().check_end_names = false;
+    let mut open: Vec<Option<usize>> = Vec::new();
+    loop {
+        let before = reader.buffer_position() as usize;
+        match reader.read_resolved_event() {
+            Ok((ns, Event::Start(e))) => {
+                open.push((is_chart(&ns) && e.local_name().as_ref() == b"ser").then_some(before));
+            }
+            Ok((_, Event::End(_))) => {
+                if let Some(Some(start)) = open.pop() {
+                    spans.push((start, reader.buffer_position() as usize));
+                }
+            }
+            Ok((_, Event::Eof)) | Err(_) => break,
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// The name of series `ser` and the placeholder and points it's bound to.
+fn bound_series<'a>(ser: &str, series: &[Series<'a>]) -> Option<(String, Series<'a>)> {
+    let (tx_start, tx_end) = element_span(ser, "c:tx")?;
+    let name = unescape_xml(&element_texts(&ser[tx_start..tx_end], "c:v").concat());
+    let &bound = series.iter().find(|(p, _)| name.contains(p))?;
+    Some((name, bound))
+}
+
+/// Category ranges filled from bound series, as `(formula, new formula, point cache)`.
+struct FilledCategories(Vec<(String, String, String)>);
+
+impl FilledCategories {
+    /// The new `c:cat` of a series taking its categories from `formula`.
+    fn get(&self, formula: &str) -> Option<String> {
+        let (_, formula, cache) = self.0.iter().find(|(f, _, _)| f == formula)?;
+        Some(format!("<c:cat><c:strRef><c:f>{}</c:f><c:strCache>{}</c:strCache></c:strRef></c:cat>", formula, cache))
+    }
+}
+
+/// Fills the category ranges of the bound series in a chart.
+///
+/// Series usually share one range of categories, so each range is written once,
+/// with the labels of the longest series taking categories from it: where series
+/// label the same point differently, the first series' label is kept. Every
+/// series on the range, bound or not, then shows the same categories.
+fn fill_categories(xml: &str, series: &[Series], updates: &mut Vec<CellUpdate>) -> FilledCategories {
+    let mut ranges: Vec<(String, Vec<String>)> = Vec::new();
+    for (start, end) in series_spans(xml) {
+        let ser = &xml[start..end];
+        let Some((_, (_, points))) = bound_series(ser, series) else {
+            continue;
+        };
+        let Some(cat) = element_span(ser, "c:cat") else {
+            continue;
+        };
+        let Some(formula) = element_texts(&ser[cat.0..cat.1], "c:f").into_iter().next() else {
+            continue;
+        };
+        if RangeRef::parse(&formula).is_none() {
+            continue;
+        }
+        let index = match ranges.iter().position(|(f, _)| *f == formula) {
+            Some(index) => index,
+            None => {
+                ranges.push((formula, Vec::new()));
+                ranges.len() - 1
+            }
+        };
+        let labels = &mut ranges[index].1;
+        let known = labels.len();
+        labels.extend(points.iter().skip(known).map(|(label, _)| label.clone()));
+    }
+
+    let filled = ranges.into_iter().filter_map(|(formula, labels)| {
+        let labels = labels.into_iter().map(CellValue::Text).collect();
+        let (new_formula, cache) = fill_range(Some(&formula), labels, updates);
+        Some((formula, new_formula?, cache))
+    });
+    FilledCategories(filled.collect())
+}
+
+fn fill_series(
+    ser: &str,
+    series: &[Series],
+    categories: &FilledCategories,
+    updates: &mut Vec<CellUpdate>,
+) -> Option<String> {
+    let cat = element_span(ser, "c:cat");
+    let cat_formula = cat.and_then(|cat| element_texts(&ser[cat.0..cat.1], "c:f").into_iter().next());
+    let shared_cat = cat_formula.as_deref().and_then(|f| categories.get(f));
+    let Some((name, (placeholder, points))) = bound_series(ser, series) else {
+        // Unbound series show the categories filled from the others
+        return shared_cat.map(|cat_xml| splice(ser, "c:cat", &cat_xml));
+    };
+    let (tx_start, tx_end) = element_span(ser, "c:tx")?;
+    let tx = &ser[tx_start..tx_end];
+    let display_name = name.replacen(placeholder, "", 1).trim().to_string();
+
+    let mut filled = ser.to_string();
+    if let Some(cat_xml) = shared_cat {
+        filled = splice(&filled, "c:cat", &cat_xml);
+    } else if cat.is_some() {
+        let labels = points.iter().map(|(label, _)| CellValue::Text(label.clone()));
+        let (formula, cache) = fill_range(cat_formula.as_deref(), labels.collect(), updates);
+        let cat_xml = match formula {
+            Some(f) => format!("<c:cat><c:strRef><c:f>{}</c:f><c:strCache>{}</c:strCache></c:strRef></c:cat>", f, cache),
+            None => format!("<c:cat><c:strLit>{}</c:strLit></c:cat>", cache),
+        };
+        filled = splice(&filled, "c:cat", &cat_xml);
+    }
+    if let Some(val) = element_span(ser, "c:val") {
+        let val_slice = &ser[val.0..val.1];
+        let formula = element_texts(val_slice, "c:f").into_iter().next();
+        let format_code = element_texts(val_slice, "c:formatCode")
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "General".to_string());
+        let values = points.iter().map(|(_, value)| CellValue::Number(*value));
+        let (formula, cache) = fill_range(formula.as_deref(), values.collect(), updates);
+        let val_xml = match formula {
+            Some(f) => format!(
+                "<c:val><c:numRef><c:f>{}</c:f><c:numCache><c:formatCode>{}</c:formatCode>{}</c:numCache></c:numRef></c:val>",
+                f, format_code, cache
+            ),
+            None => format!("<c:val><c:numLit><c:formatCode>{}</c:formatCode>{}</c:numLit></c:val>", format_code, cache),
+        };
+        filled = splice(&filled, "c:val", &val_xml);
+    }
+
+    let tx_formula = element_texts(tx, "c:f").into_iter().next();
+    if let Some(range) = tx_formula.as_deref().and_then(RangeRef::parse) {
+        updates.push(CellUpdate {
+            sheet: range.sheet.clone(),
+            column: range.start.0,
+            row: range.start.1,
+            value: CellValue::Text(display_name.clone()),
+        });
+    }
+    let tx_filled = replace_element_texts(tx, "c:v", &escape_xml(&display_name));
+    Some(splice(&filled, "c:tx", &tx_filled))
+}
+
+/// Writes `values` into the range `formula` starts at, resized to fit, and
+/// returns the new formula and the point cache for the chart. Cells of the
+/// original range the values no longer reach are cleared.
+fn fill_range(
+    formula: Option<&str>,
+    values: Vec<CellValue>,
+    updates: &mut Vec<CellUpdate>,
+) -> (Option<String>, String) {
+    let mut cache = format!("<c:ptCount val=\"{}\"/>", values.len());
+    for (idx, value) in values.iter().enumerate() {
+        match value {
+            CellValue::Text(text) => cache.push_str(&format!("<c:pt idx=\"{}\"><c:v>{}</c:v></c:pt>", idx, escape_xml(text))),
+            CellValue::Number(n) if n.is_finite() => cache.push_str(&format!("<c:pt idx=\"{}\"><c:v>{}</c:v></c:pt>", idx, n)),
+            _ => {}
+        }
+    }
+
+    let Some(range) = formula.and_then(RangeRef::parse) else {
+        return (formula.map(str::to_string), cache);
+    };
+    let count = values.len().max(1) as u32;
+    let horizontal = range.start.1 == range.end.1 && range.start.0 != range.end.0;
+    let cell = |i: u32| if horizontal { (range.start.0 + i, range.start.1) } else { (range.start.0, range.start.1 + i) };
+
+    let old_count = if horizontal {
+        range.end.0.saturating_sub(range.start.0) + 1
+    } else {
+        range.end.1.saturating_sub(range.start.1) + 1
+    };
+    for i in values.len() as u32..old_count {
+        let (column, row) = cell(i);
+        updates.push(CellUpdate { sheet: range.sheet.clone(), column, row, value: CellValue::Empty });
+    }
+    for (i, value) in values.into_iter().enumerate() {
+        let (column, row) = cell(i as u32);
+        let value = match value {
+            CellValue::Number(n) if !n.is_finite() => CellValue::Empty,
+            value => value,
+        };
+        updates.push(CellUpdate { sheet: range.sheet.clone(), column, row, value });
+    }
+
+    let end = cell(count - 1);
+    let formula = format!(
+        "{}!${}${}:${}${}",
+        range.sheet_prefix,
+        column_letters(range.start.0),
+        range.start.1,
+        column_letters(end.0),
+        end.1
+    );
+    (Some(formula), cache)
+}
+
+/// A cell range formula like `Sheet1!$A$2:$A$5` or `'My sheet'!$B$1`.
+struct RangeRef {
+    /// The sheet part of the formula as written, quotes included.
+    sheet_prefix: String,
+    sheet: String,
+    start: (u32, u32),
+    end: (u32, u32),
+}
+
+impl RangeRef {
+    fn parse(formula: &str) -> Option<Self> {
+        let (sheet_prefix, cells) = formula.rsplit_once('!')?;
+        let (start, end) = cells.split_once(':').unwrap_or((cells, cells));
+        let sheet = unescape_xml(sheet_prefix);
+        let sheet = match sheet.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+            Some(quoted) => quoted.replace("''", "'"),
+            None => sheet,
+        };
+        Some(Self {
+            sheet_prefix: sheet_prefix.to_string(),
+            sheet,
+            start: parse_cell(start)?,
+            end: parse_cell(end)?,
+        })
+    }
+}
+
+/// Parses a cell reference like `$B$12` into a 1-based `(column, row)`.
+fn parse_cell(cell: &str) -> Option<(u32, u32)> {
+    let cell = cell.replace('$', "");
+    let digits = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, row) = cell.split_at(digits);
+    if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let column = letters.bytes().fold(0, |acc, b| acc * 26 + u32::from(b - b'A' + 1));
+    Some((column, row.parse().ok()?))
+}
+
+fn column_letters(mut column: u32) -> String {
+    let mut letters = Vec::new();
+    while column > 0 {
+        let rem = (column - 1) % 26;
+        letters.push(b'A' + rem as u8);
+        column = (column - 1) / 26;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap_or_default()
+}
+
+/// Finds the workbook embedded with a chart, following its `c:externalData`
+/// relationship. Returns the workbook's part name.
+pub(crate) fn embedded_workbook_part(chart_part: &str, chart_xml: &str, chart_rels_xml: &str) -> Option<String> {
+    let start = chart_xml.find("<c:externalData")?;
+    let id = attribute(&chart_xml[start..], "r:id")?;
+    let target = relationship_target(chart_rels_xml, id)?;
+    Some(resolve_part(chart_part, target))
+}
+
+/// Name of the relationships part of `part`, e.g. `word/charts/_rels/chart1.xml.rels`.
+pub(crate) fn rels_part(part: &str) -> String {
+    match part.rsplit_once('/') {
+        Some((dir, file)) => format!("{}/_rels/{}.rels", dir, file),
+        None => format!("_rels/{}.rels", part),
+    }
+}
+
+fn relationship_target<'a>(rels_xml: &'a str, id: &str) -> Option<&'a str> {
+    let mut rest = rels_xml;
+    while let Some(start) = rest.find("<Relationship ") {
+        let element = &rest[start..];
+        if attribute(element, "Id") == Some(id) {
+            return attribute(element, "Target");
+        }
+        rest = &rest[start + 1..];
+    }
+    None
+}
+
+/// Resolves a relationship target relative to the part that holds it.
+fn resolve_part(source_part: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = source_part.split('/').collect();
+    segments.pop();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// Writes cell updates into an embedded `.xlsx` workbook, keeping the
+/// compression and timestamps of its entries like the document's own, or
+/// stamping them with `fixed_time` in deterministic mode.
+pub(crate) fn fill_workbook(
+    xlsx: &[u8],
+    updates: &[CellUpdate],
+    fixed_time: Option<zip::DateTime>,
+) -> Result<Vec<u8>, TemplateError> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(xlsx))?;
+    let workbook = read_entry(&mut archive, "xl/workbook.xml")?;
+    let workbook_rels = read_entry(&mut archive, "xl/_rels/workbook.xml.rels")?;
+
+    let mut sheet_updates: BTreeMap<String, Vec<&CellUpdate>> = BTreeMap::new();
+    for update in updates {
+        let Some(part) = sheet_part(&workbook, &workbook_rels, &update.sheet) else {
+            continue;
+        };
+        sheet_updates.entry(part).or_default().push(update);
+    }
+
+    let mut output = Cursor::new(Vec::new());
+    let mut writer = zip::ZipWriter::new(&mut output);
+    for i in 0..archive.len() {
+        let original = archive.by_index_raw(i)?;
+        let name = original.name().to_string();
+        match (sheet_updates.get(&name), fixed_time) {
+            (Some(updates), _) => {
+                let options = entry_options(&original, fixed_time);
+                drop(original);
+                let contents = set_cells(&read_entry(&mut archive, &name)?, updates);
+                writer.start_file(&name, options)?;
+                writer.write_all(contents.as_bytes())?;
+            }
+            (None, Some(time)) => {
+                let unix_mode = original.unix_mode();
+                writer.raw_copy_file_touch(original, time, unix_mode)?;
+            }
+            (None, None) => writer.raw_copy_file(original)?,
+        }
+    }
+    writer.finish()?;
+    Ok(output.into_inner())
+}
+
+/// Part name of the worksheet called `sheet` in a workbook.
+fn sheet_part(workbook_xml: &str, workbook_rels_xml: &str, sheet: &str) -> Option<String> {
+    let mut rest = workbook_xml;
+    while let Some(start) = rest.find("<sheet ") {
+        let element = &rest[start..];
+        if attribute(element, "name").map(unescape_xml).as_deref() == Some(sheet) {
+            let target = relationship_target(workbook_rels_xml, attribute(element, "r:id")?)?;
+            return Some(resolve_part("xl/workbook.xml", target));
+        }
+        rest = &rest[start + 1..];
+    }
+    None
+}
+
+/// A row of a worksheet: its start tag and its cells by column.
+struct Row {
+    start_tag: String,
+    cells: BTreeMap<u32, String>,
+}
+
+/// Sets cell values in a worksheet. Text is written as inline strings, so the
+/// shared string table is left alone; existing cell styles are kept.
+fn set_cells(sheet_xml: &str, updates: &[&CellUpdate]) -> String {
+    let Some(data_start) = sheet_xml.find("<sheetData") else {
+        return sheet_xml.to_string();
+    };
+    let Some(tag_len) = sheet_xml[data_start..].find('>') else {
+        return sheet_xml.to_string();
+    };
+    let content_start = data_start + tag_len + 1;
+    let (content, data_end) = if sheet_xml[..content_start].ends_with("/>") {
+        ("", content_start)
+    } else {
+        match sheet_xml[content_start..].find("</sheetData>") {
+            Some(len) => (&sheet_xml[content_start..content_start + len], content_start + len + "</sheetData>".len()),
+            None => return sheet_xml.to_string(),
+        }
+    };
+
+    let mut rows = parse_rows(content);
+    for update in updates {
+        let row = rows.entry(update.row).or_insert_with(|| Row {
+            start_tag: format!("<row r=\"{}\">", update.row),
+            cells: BTreeMap::new(),
+        });
+        let reference = format!("{}{}", column_letters(update.column), update.row);
+        let style = row
+            .cells
+            .get(&update.column)
+            .and_then(|cell| attribute(cell, "s"))
+            .map(|s| format!(" s=\"{}\"", s))
+            .unwrap_or_default();
+        match &update.value {
+            CellValue::Text(text) => {
+                row.cells.insert(
+                    update.column,
+                    format!("<c r=\"{}\"{} t=\"inlineStr\"><is><t>{}</t></is></c>", reference, style, escape_xml(text)),
+                );
+            }
+            CellValue::Number(n) => {
+                row.cells.insert(update.column, format!("<c r=\"{}\"{}><v>{}</v></c>", reference, style, n));
+            }
+            CellValue::Empty => {
+                row.cells.remove(&update.column);
+            }
+        }
+    }
+
+    let mut data = String::from("<sheetData>");
+    for row in rows.values() {
+        data.push_str(&row.start_tag);
+        data.extend(row.cells.values().map(String::as_str));
+        data.push_str("</row>");
+    }
+    data.push_str("</sheetData>");
+    format!("{}{}{}", &sheet_xml[..data_start], data, &sheet_xml[data_end..])
+}
+
+fn parse_rows(content: &str) -> BTreeMap<u32, Row> {
+    let mut rows = BTreeMap::new();
+    let mut rest = content;
+    let mut last_row = 0;
+    while let Some(start) = rest.find("<row") {
+        let Some(tag_len) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start..start + tag_len + 1];
+        let row_number = attribute(tag, "r").and_then(|r| r.parse().ok()).unwrap_or(last_row + 1);
+        last_row = row_number;
+
+        // Spans are a load-time hint that may no longer hold once cells change.
+        let mut start_tag = remove_attribute(tag, "spans");
+        let body = if let Some(open) = start_tag.strip_suffix("/>") {
+            start_tag = format!("{}>", open);
+            rest = &rest[start + tag_len + 1..];
+            ""
+        } else {
+            let body_start = start + tag_len + 1;
+            let Some(body_len) = rest[body_start..].find("</row>") else {
+                break;
+            };
+            let body = &rest[body_start..body_start + body_len];
+            rest = &rest[body_start + body_len + "</row>".len()..];
+            body
+        };
+        rows.insert(row_number, Row { start_tag, cells: parse_cells(body) });
+    }
+    rows
+}
+
+fn parse_cells(body: &str) -> BTreeMap<u32, String> {
+    let mut cells = BTreeMap::new();
+    let mut rest = body;
+    let mut last_column = 0;
+    while let Some(start) = rest.find("<c") {
+        if !rest[start + 2..].starts_with([' ', '>', '/']) {
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let Some(tag_len) = rest[start..].find('>') else {
+            break;
+        };
+        let end = if rest[start..start + tag_len + 1].ends_with("/>") {
+            start + tag_len + 1
+        } else {
+            match rest[start..].find("</c>") {
+                Some(len) => start + len + "</c>".len(),
+                None => break,
+            }
+        };
+        let cell = &rest[start..end];
+        let column = attribute(cell, "r").and_then(parse_cell).map_or(last_column + 1, |(c, _)| c);
+        last_column = column;
+        cells.insert(column, cell.to_string());
+        rest = &rest[end..];
+    }
+    cells
+}
+
+fn remove_attribute(tag: &str, name: &str) -> String {
+    let key = format!(" {}=\"", name);
+    let Some(start) = tag.find(&key) else {
+        return tag.to_string();
+    };
+    let value_start = start + key.len();
+    match tag[value_start..].find('"') {
+        Some(len) => format!("{}{}", &tag[..start], &tag[value_start + len + 1..]),
+        None => tag.to_string(),
+    }
+}
+
+/// Start and end offsets of the first `name` element, tags included.
+fn element_span(xml: &str, name: &str) -> Option<(usize, usize)> {
+    let start = xml.find(&format!("<{}>", name))?;
+    let close = format!("</{}>", name);
+    let end = start + xml[start..].find(&close)? + close.len();
+    Some((start, end))
+}
+
+/// Replaces the first `name` element with `replacement`.
+fn splice(xml: &str, name: &str, replacement: &str) -> String {
+    match element_span(xml, name) {
+        Some((start, end)) => format!("{}{}{}", &xml[..start], replacement, &xml[end..]),
+        None => xml.to_string(),
+    }
+}
+
+/// Text content of every `name` element, as written in the XML.
+fn element_texts(xml: &str, name: &str) -> Vec<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let mut texts = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let content_start = start + open.len();
+        let Some(len) = rest[content_start..].find(&close) else {
+            break;
+        };
+        texts.push(rest[content_start..content_start + len].to_string());
+        rest = &rest[content_start + len..];
+    }
+    texts
+}
+
+/// Sets the text of the first `name` element to `value` and empties the rest.
+fn replace_element_texts(xml: &str, name: &str, value: &str) -> String {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let mut result = String::with_capacity(xml.len());
+    let mut rest = xml;
+    let mut value = Some(value);
+    while let Some(start) = rest.find(&open) {
+        let content_start = start + open.len();
+        let Some(len) = rest[content_start..].find(&close) else {
+            break;
+        };
+        result.push_str(&rest[..content_start]);
+        result.push_str(value.take().unwrap_or_default());
+        rest = &rest[content_start + len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART: &str = concat!(
+        r#"<c:chartSpace><c:chart><c:plotArea><c:barChart>"#,
+        r#"<c:ser><c:idx val="0"/><c:tx><c:strRef><c:f>Sheet1!$B$1</c:f><c:strCache><c:ptCount val="1"/>"#,
+        r#"<c:pt idx="0"><c:v>Revenue {revenue}</c:v></c:pt></c:strCache></c:strRef></c:tx>"#,
+        r#"<c:cat><c:strRef><c:f>Sheet1!$A$2:$A$4</c:f><c:strCache><c:ptCount val="3"/></c:strCache></c:strRef></c:cat>"#,
+        r#"<c:val><c:numRef><c:f>Sheet1!$B$2:$B$4</c:f><c:numCache><c:formatCode>#,##0</c:formatCode><c:ptCount val="3"/></c:numCache></c:numRef></c:val>"#,
+        r#"</c:ser>"#,
+        r#"<c:ser><c:idx val="1"/><c:tx><c:v>Unbound</c:v></c:tx></c:ser>"#,
+        r#"</c:barChart></c:plotArea></c:chart><c:externalData r:id="rId3"/></c:chartSpace>"#,
+    );
+
+    #[test]
+    fn fills_bound_series() {
+        let points = vec![("Jan & Feb".to_string(), 10.0), ("Mar".to_string(), 2.5)];
+        let (xml, updates) = fill_chart(CHART, &[("{revenue}", &points)]);
+        assert!(xml.contains("<c:v>Revenue</c:v>"), "{}", xml);
+        assert!(xml.contains(concat!(
+            r#"<c:cat><c:strRef><c:f>Sheet1!$A$2:$A$3</c:f><c:strCache><c:ptCount val="2"/>"#,
+            r#"<c:pt idx="0"><c:v>Jan &amp; Feb</c:v></c:pt><c:pt idx="1"><c:v>Mar</c:v></c:pt>"#,
+        )), "{}", xml);
+        assert!(xml.contains(concat!(
+            r#"<c:numCache><c:formatCode>#,##0</c:formatCode><c:ptCount val="2"/>"#,
+            r#"<c:pt idx="0"><c:v>10</c:v></c:pt><c:pt idx="1"><c:v>2.5</c:v></c:pt></c:numCache>"#,
+        )), "{}", xml);
+        assert!(xml.contains("<c:v>Unbound</c:v>"), "{}", xml);
+
+        // Series name, two labels, two values, plus the cleared fourth row of both columns
+        assert_eq!(updates.len(), 7);
+        assert!(updates.iter().any(|u| (u.column, u.row) == (1, 4) && matches!(u.value, CellValue::Empty)));
+    }
+
+    #[test]
+    fn fills_shared_categories_once() {
+        let ser = |name: &str, column: &str| {
+            format!(
+                concat!(
+                    r#"<c:ser><c:tx><c:v>{}</c:v></c:tx>"#,
+                    r#"<c:cat><c:strRef><c:f>Sheet1!$A$2:$A$4</c:f><c:strCache><c:ptCount val="3"/></c:strCache></c:strRef></c:cat>"#,
+                    r#"<c:val><c:numRef><c:f>Sheet1!${}$2:${}$4</c:f><c:numCache><c:ptCount val="3"/></c:numCache></c:numRef></c:val></c:ser>"#,
+                ),
+                name, column, column
+            )
+        };
+        let xml = format!("{}{}{}", ser("{long}", "B"), ser("{short}", "C"), ser("Unbound", "D"));
+        let long = vec![("Jan".to_string(), 1.0), ("Feb".to_string(), 2.0), ("Mar".to_string(), 3.0), ("Apr".to_string(), 4.0)];
+        let short = vec![("Jan".to_string(), 5.0)];
+        let (xml, updates) = fill_chart(&xml, &[("{short}", &short), ("{long}", &long)]);
+
+        assert_eq!(xml.matches("<c:f>Sheet1!$A$2:$A$5</c:f>").count(), 3, "{}", xml);
+        assert_eq!(xml.matches(r#"<c:pt idx="3"><c:v>Apr</c:v></c:pt>"#).count(), 3, "{}", xml);
+        let category_cells: Vec<_> = updates.iter().filter(|u| u.column == 1).collect();
+        assert_eq!(category_cells.len(), 4);
+        assert!(category_cells.iter().all(|u| matches!(u.value, CellValue::Text(_))));
+    }
+
+    #[test]
+    fn finds_series_by_namespace() {
+        let xml = concat!(
+            r#"<ch:chartSpace xmlns:ch="http://purl.oclc.org/ooxml/drawingml/chart"><ch:plotArea>"#,
+            r#"<ch:ser><ch:idx val="0"/></ch:ser><ch:serAx/><x:ser xmlns:x="urn:other"/></ch:plotArea></ch:chartSpace>"#,
+        );
+        let spans: Vec<_> = series_spans(xml).into_iter().map(|(start, end)| &xml[start..end]).collect();
+        assert_eq!(spans, [r#"<ch:ser><ch:idx val="0"/></ch:ser>"#]);
+    }
+
+    #[test]
+    fn parses_ranges() {
+        let range = RangeRef::parse("'Q''3 data'!$AA$2:$AC$2").unwrap();
+        assert_eq!(range.sheet, "Q'3 data");
+        assert_eq!((range.start, range.end), ((27, 2), (29, 2)));
+        assert_eq!(column_letters(27), "AA");
+        assert_eq!(column_letters(26), "Z");
+    }
+
+    #[test]
+    fn finds_embedded_workbook() {
+        let rels = r#"<Relationships><Relationship Id="rId3" Type="package" Target="../embeddings/Microsoft_Excel_Worksheet.xlsx"/></Relationships>"#;
+        assert_eq!(rels_part("word/charts/chart1.xml"), "word/charts/_rels/chart1.xml.rels");
+        assert_eq!(
+            embedded_workbook_part("word/charts/chart1.xml", CHART, rels).as_deref(),
+            Some("word/embeddings/Microsoft_Excel_Worksheet.xlsx")
+        );
+    }
+
+    #[test]
+    fn sets_cells_keeping_styles() {
+        let sheet = concat!(
+            r#"<worksheet><dimension ref="A1:B3"/><sheetData>"#,
+            r#"<row r="1" spans="1:2"><c r="A1" t="s"><v>0</v></c><c r="B1" s="2" t="s"><v>1</v></c></row>"#,
+            r#"<row r="3"><c r="A3"><v>7</v></c></row>"#,
+            r#"</sheetData></worksheet>"#,
+        );
+        let update = |column, row, value| CellUpdate { sheet: "Sheet1".into(), column, row, value };
+        let updates = [
+            update(2, 1, CellValue::Text("Revenue".into())),
+            update(2, 2, CellValue::Number(12.5)),
+            update(1, 3, CellValue::Empty),
+        ];
+        let result = set_cells(sheet, &updates.iter().collect::<Vec<_>>());
+        assert_eq!(
+            result,
+            concat!(
+                r#"<worksheet><dimension ref="A1:B3"/><sheetData>"#,
+                r#"<row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" s="2" t="inlineStr"><is><t>Revenue</t></is></c></row>"#,
+                r#"<row r="2"><c r="B2"><v>12.5</v></c></row>"#,
+                r#"<row r="3"></row>"#,
+                r#"</sheetData></worksheet>"#,
+            )
+        );
+    }
+}
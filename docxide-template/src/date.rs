@@ -0,0 +1,189 @@
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September",
+    "October", "November", "December",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday",
+];
+
+/// A calendar date, used for fields backed by a date picker content control.
+///
+/// The value is rendered with the picker's `w:dateFormat` (e.g. `dd.MM.yyyy`),
+/// using English month and weekday names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Creates a date, returning `None` if the month or day is out of range.
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    pub fn year(&self) -> i32 { self.year }
+
+    pub fn month(&self) -> u8 { self.month }
+
+    pub fn day(&self) -> u8 { self.day }
+
+    /// Parses an ISO 8601 date, `yyyy-MM-dd`.
+    #[cfg(feature = "serde")]
+    fn parse_iso(text: &str) -> Option<Self> {
+        let mut parts = text.split('-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        Self::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+    }
+
+    /// Day of the week, `0` for Sunday through `6` for Saturday.
+    pub fn weekday(&self) -> u8 {
+        const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let y = if self.month < 3 { self.year - 1 } else { self.year };
+        let w = y + y.div_euclid(4) - y.div_euclid(100) + y.div_euclid(400)
+            + OFFSETS[self.month as usize - 1]
+            + self.day as i32;
+        w.rem_euclid(7) as u8
+    }
+
+    /// Formats the date using Word's date picture syntax.
+    ///
+    /// Supports `d`, `dd`, `ddd`, `dddd`, `M`, `MM`, `MMM`, `MMMM`, `yy` and
+    /// `yyyy`. A date has no time of day, so the time tokens `h`, `hh`, `H`,
+    /// `HH`, `m`, `mm`, `s`, `ss` and `am/pm` render midnight, as Word shows
+    /// a date picked without a time. Text in single quotes is copied verbatim,
+    /// as is any other character; a quote without a closing one is kept as is.
+    pub fn format(&self, pattern: &str) -> String {
+        let mut out = String::new();
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c == '\'' {
+                if let Some(end) = chars[i + 1..].iter().position(|&c| c == '\'').map(|p| i + 1 + p) {
+                    out.extend(&chars[i + 1..end]);
+                    i = end + 1;
+                    continue;
+                }
+            }
+            let rest: String = chars[i..chars.len().min(i + 5)].iter().collect();
+            if rest.eq_ignore_ascii_case("am/pm") {
+                out.push_str(if c == 'A' { "AM" } else { "am" });
+                i += 5;
+                continue;
+            }
+            let run = chars[i..].iter().take_while(|&&n| n == c).count();
+            match (c, run) {
+                ('d', 1) => out.push_str(&self.day.to_string()),
+                ('d', 2) => out.push_str(&format!("{:02}", self.day)),
+                ('d', 3) => out.push_str(&WEEKDAY_NAMES[self.weekday() as usize][..3]),
+                ('d', _) => out.push_str(WEEKDAY_NAMES[self.weekday() as usize]),
+                ('M', 1) => out.push_str(&self.month.to_string()),
+                ('M', 2) => out.push_str(&format!("{:02}", self.month)),
+                ('M', 3) => out.push_str(&MONTH_NAMES[self.month as usize - 1][..3]),
+                ('M', _) => out.push_str(MONTH_NAMES[self.month as usize - 1]),
+                ('y', 1..=2) => out.push_str(&format!("{:02}", self.year.rem_euclid(100))),
+                ('y', _) => out.push_str(&format!("{:04}", self.year)),
+                ('h', _) => out.push_str("12"),
+                ('H' | 'm' | 's', 1) => out.push('0'),
+                ('H' | 'm' | 's', _) => out.push_str("00"),
+                _ => out.extend(&chars[i..i + run]),
+            }
+            i += run;
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Dates serialize as ISO 8601 strings, `yyyy-MM-dd`, like their `Display`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Date {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Date {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Date::parse_iso(&text)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid date `{}`, expected yyyy-MM-dd", text)))
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_dates() {
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::new(2024, 13, 1).is_none());
+        assert!(Date::new(2024, 4, 31).is_none());
+        assert!(Date::new(2024, 1, 0).is_none());
+    }
+
+    #[test]
+    fn weekday_of_known_dates() {
+        assert_eq!(Date::new(2024, 3, 5).unwrap().weekday(), 2);
+        assert_eq!(Date::new(2000, 1, 1).unwrap().weekday(), 6);
+        assert_eq!(Date::new(1970, 1, 1).unwrap().weekday(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_as_iso_dates() {
+        let date = Date::new(2024, 3, 5).unwrap();
+        assert_eq!(serde_json::to_string(&date).unwrap(), r#""2024-03-05""#);
+        assert_eq!(serde_json::from_str::<Date>(r#""2024-03-05""#).unwrap(), date);
+        assert!(serde_json::from_str::<Date>(r#""2024-02-30""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""2024-3-5-1""#).is_err());
+    }
+
+    #[test]
+    fn formats_word_date_pictures() {
+        let date = Date::new(2024, 3, 5).unwrap();
+        let cases = vec![
+            ("M/d/yyyy", "3/5/2024"),
+            ("dd.MM.yyyy", "05.03.2024"),
+            ("yyyy-MM-dd", "2024-03-05"),
+            ("dddd, MMMM d, yyyy", "Tuesday, March 5, 2024"),
+            ("ddd d MMM yy", "Tue 5 Mar 24"),
+            ("d 'of' MMMM", "5 of March"),
+            ("M/d/yyyy h:mm am/pm", "3/5/2024 12:00 am"),
+            ("dd.MM.yyyy HH:mm:ss", "05.03.2024 00:00:00"),
+            ("d MMM H:m:s AM/PM", "5 Mar 0:0:0 AM"),
+            ("d 'o''clock", "5 o'clock"),
+            ("d 'o'clock MMM", "5 oclock Mar"),
+            ("MMM d, yyyy'", "Mar 5, 2024'"),
+        ];
+        for (pattern, expected) in cases {
+            assert_eq!(date.format(pattern), expected, "format({:?})", pattern);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Templates whose placeholders are only known at runtime.
+
+use std::collections::HashMap;
+use std::io::{Seek, Write};
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use crate::{replace_placeholders_in_image_descriptions, replace_placeholders_in_located, replace_placeholders_in_spans};
+use crate::{PreparedTemplate, RenderOptions, TemplateError};
+
+/// A `.docx` template loaded at runtime, for templates that aren't known when
+/// compiling, such as ones uploaded by users. Placeholders are found when the
+/// template is loaded and filled from name/value pairs:
+///
+/// ```rust,ignore
+/// let template = DynamicTemplate::from_path("uploads/letter.docx")?;
+/// assert_eq!(template.placeholders(), ["firstName", "company"]);
+///
+/// let values = HashMap::from([("firstName", "Alice"), ("company", "Acme")]);
+/// let bytes = template.render(&values, &RenderOptions::default())?;
+/// ```
+///
+/// Placeholder names are used as written between the braces, with surrounding
+/// whitespace trimmed, so `{firstName}` and `{ firstName }` are both filled by
+/// `firstName`. Placeholders without a value are left in the document as is.
+/// Chart series names aren't filled.
+pub struct DynamicTemplate {
+    prepared: PreparedTemplate,
+    /// Every distinct placeholder as written, e.g. `{ firstName }`, and its name.
+    placeholders: Vec<(String, String)>,
+}
+
+/// A backslash before a brace renders the brace literally.
+const ESCAPES: [(&str, &str); 2] = [("\\{", "{"), ("\\}", "}")];
+
+impl DynamicTemplate {
+    pub fn from_bytes(template_bytes: &[u8]) -> Result<Self, TemplateError> {
+        Ok(Self::new(PreparedTemplate::from_bytes(template_bytes)?))
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, TemplateError> {
+        Ok(Self::new(PreparedTemplate::from_path(path)?))
+    }
+
+    fn new(prepared: PreparedTemplate) -> Self {
+        let placeholders = template_placeholders(&prepared);
+        Self { prepared, placeholders }
+    }
+
+    /// The names of the template's placeholders, in the order they first
+    /// appear. Section markers like `{#items}` and `{/items}` and the current
+    /// item `{.}` aren't listed.
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for (_, name) in &self.placeholders {
+            let marker = name.starts_with(['#', '/']) || name == ".";
+            if !marker && !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// Fills the placeholders from `values`, name/value pairs such as a
+    /// `HashMap<String, String>`, and returns the `.docx` bytes.
+    pub fn render<I, K, V>(&self, values: I, options: &RenderOptions) -> Result<Vec<u8>, TemplateError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let values = collect_values(values);
+        self.prepared.render_values(&self.replacements(&values), &[], options)
+    }
+
+    /// Fills the placeholders from `values` and writes the `.docx` to `writer`.
+    pub fn write_to<I, K, V, W>(&self, values: I, writer: W, options: &RenderOptions) -> Result<(), TemplateError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+        W: Write + Seek,
+    {
+        let values = collect_values(values);
+        self.prepared.render_values_to(writer, &self.replacements(&values), &[], options)
+    }
+
+    /// Fills the placeholders from `values` and writes the `.docx` to `path`.
+    pub fn save<I, K, V, P>(&self, values: I, path: P, options: &RenderOptions) -> Result<(), TemplateError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+        P: AsRef<Path>,
+    {
+        crate::__private::write_output(path.as_ref(), |writer| self.write_to(values, writer, options))
+    }
+
+    /// Fills the placeholders from the fields of `data`, any value that
+    /// serializes to a struct or map. Nested fields fill dotted placeholders,
+    /// like `{customer.name}`, and sequences fill sections repeated per item,
+    /// between `{#items}` and `{/items}`. Inside a section, placeholders name
+    /// fields of the item, or `{.}` for the item itself.
+    ///
+    /// Section markers in different cells of a table row repeat the row.
+    /// Markers in different paragraphs repeat the paragraphs between them,
+    /// leaving out the paragraphs holding the markers. Anywhere else the
+    /// content between the markers is repeated. A section for a value that
+    /// isn't a sequence renders once, or not at all when it's `false`, `null`
+    /// or missing.
+    #[cfg(feature = "serde")]
+    pub fn render_with<T: serde::Serialize + ?Sized>(
+        &self,
+        data: &T,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, TemplateError> {
+        let mut output = std::io::Cursor::new(Vec::new());
+        self.write_with(data, &mut output, options)?;
+        Ok(output.into_inner())
+    }
+
+    /// Fills the placeholders from the fields of `data`, like
+    /// [`render_with`](Self::render_with), and writes the `.docx` to `writer`.
+    #[cfg(feature = "serde")]
+    pub fn write_with<T: serde::Serialize + ?Sized, W: Write + Seek>(
+        &self,
+        data: &T,
+        writer: W,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        write_data(&self.prepared, &self.placeholders, data, writer, options)
+    }
+
+    /// Fills the placeholders from the fields of `data`, like
+    /// [`render_with`](Self::render_with), and writes the `.docx` to `path`.
+    #[cfg(feature = "serde")]
+    pub fn save_with<T: serde::Serialize + ?Sized, P: AsRef<Path>>(
+        &self,
+        data: &T,
+        path: P,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        crate::__private::write_output(path.as_ref(), |writer| self.write_with(data, writer, options))
+    }
+
+    fn replacements<'a>(&'a self, values: &HashMap<String, String>) -> Vec<(&'a str, String)> {
+        self.placeholders
+            .iter()
+            .filter_map(|(placeholder, name)| Some((placeholder.as_str(), values.get(name)?.clone())))
+            .chain(ESCAPES.iter().map(|&(escape, literal)| (escape, literal.to_string())))
+            .collect()
+    }
+}
+
+fn collect_values<I, K, V>(values: I) -> HashMap<String, String>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    values.into_iter().map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string())).collect()
+}
+
+/// Every distinct `{name}` placeholder of `prepared` as written, e.g.
+/// `{ firstName }`, and its name.
+pub(crate) fn template_placeholders(prepared: &PreparedTemplate) -> Vec<(String, String)> {
+    let mut placeholders: Vec<(String, String)> = Vec::new();
+    for text in prepared.searchable_text() {
+        for (placeholder, name) in find_placeholders(&text) {
+            if !placeholders.iter().any(|(p, _)| p == placeholder) {
+                placeholders.push((placeholder.to_string(), name.to_string()));
+            }
+        }
+    }
+    placeholders
+}
+
+/// Renders `prepared` filled from the fields of `data` and writes it to
+/// `writer`, expanding sections for `placeholders` as found by
+/// [`template_placeholders`].
+#[cfg(feature = "serde")]
+pub(crate) fn write_data<T: serde::Serialize + ?Sized, W: Write + Seek>(
+    prepared: &PreparedTemplate,
+    placeholders: &[(String, String)],
+    data: &T,
+    writer: W,
+    options: &RenderOptions,
+) -> Result<(), TemplateError> {
+    use crate::sections::{expand_sections, resolve, text, TokenDelimiters};
+
+    let root = serde_json::to_value(data).map_err(|e| TemplateError::InvalidData(e.to_string()))?;
+    if !root.is_object() {
+        return Err(TemplateError::InvalidData("expected a struct or map".to_string()));
+    }
+
+    let values: Vec<(&str, String)> = placeholders
+        .iter()
+        .filter_map(|(placeholder, name)| Some((placeholder.as_str(), text(resolve(name, &[&root])?)?)))
+        .chain(ESCAPES.iter().map(|&(escape, literal)| (escape, literal.to_string())))
+        .collect();
+    let replacements: Vec<(&str, &str)> = values.iter().map(|(p, v)| (*p, v.as_str())).collect();
+
+    prepared.render_to(writer, &replacements, &[], options, |xml, located| {
+        // A part holding every private use character has none left for
+        // tokens, so it's filled without expanding sections.
+        let Some(delimiters) = TokenDelimiters::for_part(xml) else {
+            return replace_placeholders_in_located(xml, located, &replacements);
+        };
+        let tokens: Vec<(&str, String)> = placeholders
+            .iter()
+            .map(|(placeholder, _)| (placeholder.as_str(), delimiters.token(placeholder)))
+            .chain(ESCAPES.iter().map(|&(escape, literal)| (escape, literal.to_string())))
+            .collect();
+        let tokens: Vec<(&str, &str)> = tokens.iter().map(|(p, t)| (*p, t.as_str())).collect();
+        let filled = expand_sections(&replace_placeholders_in_spans(xml, located, &tokens), delimiters, &root);
+        replace_placeholders_in_image_descriptions(&filled, &replacements)
+    })
+}
+
+/// Finds `{name}` placeholders in `text` as `(placeholder, name)`, skipping
+/// escaped braces and placeholders with a blank name.
+fn find_placeholders(text: &str) -> Vec<(&str, &str)> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find(['\\', '{']) {
+        if rest[pos..].starts_with('\\') {
+            let escaped = rest[pos + 1..].starts_with(['{', '}']);
+            rest = &rest[pos + if escaped { 2 } else { 1 }..];
+            continue;
+        }
+        let Some(len) = rest[pos + 1..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[pos..pos + len + 2];
+        let name = placeholder[1..placeholder.len() - 1].trim();
+        if !name.is_empty() {
+            found.push((placeholder, name));
+        }
+        rest = &rest[pos + len + 2..];
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_placeholders_and_skips_escapes() {
+        let found = find_placeholders(r"Dear { first name }, \{not\} {} {id}{ }{x");
+        assert_eq!(found, vec![("{ first name }", "first name"), ("{id}", "id")]);
+    }
+
+    #[test]
+    fn renders_from_name_value_pairs() {
+        let template_path = Path::new("../test-crate/templates/HelloWorld.docx");
+        if !template_path.exists() {
+            return;
+        }
+        let template = DynamicTemplate::from_path(template_path).unwrap();
+        assert_eq!(template.placeholders(), ["firstName", "productName"]);
+
+        let values = HashMap::from([("firstName", "Alice"), ("productName", "Acme")]);
+        let bytes = template.render(&values, &RenderOptions::default()).unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let xml = crate::read_entry(&mut archive, "word/document.xml").unwrap();
+        assert!(xml.contains("Alice"));
+        assert!(xml.contains("Acme"));
+        assert!(!xml.contains("firstName"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn renders_serialized_data_with_sections() {
+        use std::io::{Cursor, Read};
+
+        let template_path = Path::new("../test-crate/templates/HelloWorld.docx");
+        if !template_path.exists() {
+            return;
+        }
+        let body = concat!(
+            r#"<w:p><w:r><w:t>Order for {cust</w:t></w:r><w:r><w:t>omer.name}</w:t></w:r></w:p>"#,
+            r#"<w:tbl><w:tr><w:tc><w:p><w:r><w:t>{#lines}{product}</w:t></w:r></w:p></w:tc>"#,
+            r#"<w:tc><w:p><w:r><w:t>{qty}{/lines}</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+        );
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+            body
+        );
+        let mut template = zip::ZipArchive::new(Cursor::new(std::fs::read(template_path).unwrap())).unwrap();
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for i in 0..template.len() {
+            let mut file = template.by_index(i).unwrap();
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents).unwrap();
+            if file.name() == "word/document.xml" {
+                contents = document.clone().into_bytes();
+            }
+            writer.start_file(file.name(), zip::write::SimpleFileOptions::default()).unwrap();
+            writer.write_all(&contents).unwrap();
+        }
+        let template_bytes = writer.finish().unwrap().into_inner();
+
+        let data = serde_json::json!({
+            "customer": { "name": "Acme & Co" },
+            "lines": [{ "product": "Pen", "qty": 2 }, { "product": "Ink", "qty": 1 }],
+        });
+        let template = DynamicTemplate::from_bytes(&template_bytes).unwrap();
+        assert_eq!(template.placeholders(), ["customer.name", "product", "qty"]);
+        let options = RenderOptions::new().deterministic(true);
+        let bytes = template.render_with(&data, &options).unwrap();
+        let prepared = PreparedTemplate::from_bytes(&template_bytes).unwrap();
+        assert_eq!(prepared.render_with(&data, &options).unwrap(), bytes);
+        let path = std::env::temp_dir().join(format!("docxide-render-with-{}.docx", std::process::id()));
+        prepared.save_with(&data, &path, &options).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), bytes);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let xml = crate::read_entry(&mut archive, "word/document.xml").unwrap();
+        assert!(xml.contains("<w:t>Order for Acme &amp; Co</w:t></w:r><w:r><w:t></w:t>"));
+        let row = |product: &str, qty: &str| {
+            format!(
+                "<w:tr><w:tc><w:p><w:r><w:t>{}</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>{}</w:t></w:r></w:p></w:tc></w:tr>",
+                product, qty
+            )
+        };
+        assert!(xml.contains(&format!("<w:tbl>{}{}</w:tbl>", row("Pen", "2"), row("Ink", "1"))));
+
+        assert!(matches!(template.render_with(&[1, 2], &RenderOptions::default()), Err(TemplateError::InvalidData(_))));
+    }
+}
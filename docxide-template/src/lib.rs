@@ -1,12 +1,28 @@
 //! Type-safe `.docx` template engine.
 //!
 //! Use [`generate_templates!`] to scan a directory of `.docx` files at compile time
-//! and generate a struct per template. See the [README](https://github.com/sverrejb/docxide-template)
+//! and generate a struct per template, or [`derive@DocxTemplate`] to check a struct
+//! you write yourself against its template. See the [README](https://github.com/sverrejb/docxide-template)
 //! for full usage instructions.
 
-pub use docxide_template_derive::generate_templates;
+mod chart;
+mod date;
+mod dynamic;
+mod options;
+mod prepared;
+mod properties;
+#[cfg(feature = "serde")]
+mod sections;
 
-use std::io::{Cursor, Read, Write};
+pub use date::Date;
+pub use dynamic::DynamicTemplate;
+pub use options::RenderOptions;
+pub use prepared::PreparedTemplate;
+pub use docxide_template_derive::{generate_templates, DocxTemplate};
+
+use docxide_template_core::{decode_percent_braces, find_matches, span_edits, TextSpan, TEXT_ELEMENTS};
+use std::borrow::Cow;
+use std::io::{Read, Seek, Write};
 use std::path::Path;
 
 /// Error type returned by template `save()` and `to_bytes()` methods.
@@ -16,6 +32,9 @@ pub enum TemplateError {
     Io(std::io::Error),
     /// The `.docx` template is malformed (bad zip archive, invalid XML encoding).
     InvalidTemplate(String),
+    /// The values to render couldn't be used, e.g. data that doesn't serialize
+    /// to a struct or map.
+    InvalidData(String),
 }
 
 impl std::fmt::Display for TemplateError {
@@ -23,6 +42,19 @@ impl std::fmt::Display for TemplateError {
         match self {
             Self::Io(e) => write!(f, "{}", e),
             Self::InvalidTemplate(msg) => write!(f, "invalid template: {}", msg),
+            Self::InvalidData(msg) => write!(f, "invalid data: {}", msg),
+        }
+    }
+}
+
+impl TemplateError {
+    /// A copy of the error, of the same variant. I/O errors keep their kind and
+    /// message but lose their source.
+    fn duplicate(&self) -> Self {
+        match self {
+            Self::Io(e) => Self::Io(std::io::Error::new(e.kind(), e.to_string())),
+            Self::InvalidTemplate(msg) => Self::InvalidTemplate(msg.clone()),
+            Self::InvalidData(msg) => Self::InvalidData(msg.clone()),
         }
     }
 }
@@ -31,7 +63,7 @@ impl std::error::Error for TemplateError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::Io(e) => Some(e),
-            Self::InvalidTemplate(_) => None,
+            Self::InvalidTemplate(_) | Self::InvalidData(_) => None,
         }
     }
 }
@@ -53,10 +85,54 @@ impl From<std::string::FromUtf8Error> for TemplateError {
     fn from(e: std::string::FromUtf8Error) -> Self { Self::InvalidTemplate(e.to_string()) }
 }
 
+/// Error returned by a generated builder's `build()` when fields weren't set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFields {
+    fields: Vec<&'static str>,
+}
+
+impl MissingFields {
+    #[doc(hidden)]
+    pub fn new(fields: Vec<&'static str>) -> Self {
+        Self { fields }
+    }
+
+    /// Names of the fields that weren't set, in declaration order.
+    pub fn fields(&self) -> &[&'static str] {
+        &self.fields
+    }
+}
+
+impl std::fmt::Display for MissingFields {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "missing fields: {}", self.fields.join(", "))
+    }
+}
+
+impl std::error::Error for MissingFields {}
+
 #[doc(hidden)]
 pub trait DocxTemplate: __private::Sealed {
     fn template_path(&self) -> &Path;
-    fn replacements(&self) -> Vec<(&str, &str)>;
+    fn replacements(&self) -> Vec<(&str, Cow<'_, str>)>;
+
+    /// Chart series placeholders and the points to fill them with.
+    fn chart_series(&self) -> Vec<(&str, &[(String, f64)])> {
+        Vec::new()
+    }
+}
+
+/// Implemented by the enums generated for dropdown lists, whose values can
+/// only be entries of the list. A struct deriving `DocxTemplate` fills a
+/// dropdown from one of them, checked at compile time to hold no value the
+/// template's list is missing.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't fill a dropdown, which only takes the entries of its list",
+    note = "use the enum `generate_templates!` generates for the dropdown, or a combo box in the template to allow free text"
+)]
+pub trait DropdownValue: __private::Sealed + std::fmt::Display {
+    /// The text of every entry a value can be.
+    const ENTRIES: &'static [&'static str];
 }
 
 #[doc(hidden)]
@@ -65,143 +141,615 @@ pub mod __private {
 
     pub trait Sealed {}
 
+    /// Fails to compile when used in a constant if `T` has an entry that
+    /// isn't in the dropdown's `entries`.
+    pub const fn check_dropdown<T: DropdownValue>(entries: &[&str]) {
+        let mut i = 0;
+        while i < T::ENTRIES.len() {
+            let mut j = 0;
+            while j < entries.len() && !str_eq(T::ENTRIES[i], entries[j]) {
+                j += 1;
+            }
+            if j == entries.len() {
+                panic!("the dropdown's list is missing an entry of the field's type");
+            }
+            i += 1;
+        }
+    }
+
+    const fn str_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    /// Deserializes a generated struct from JSON.
+    #[cfg(feature = "serde")]
+    pub fn from_json<T: serde::de::DeserializeOwned>(json: &str) -> Result<T, TemplateError> {
+        serde_json::from_str(json).map_err(|e| TemplateError::InvalidData(e.to_string()))
+    }
+
+    /// Converts any serializable struct or map into a generated struct. Nested
+    /// fields and sequence items are also offered under dotted names, so a
+    /// `{customer.name}` placeholder is filled from `customer: { name }`.
+    #[cfg(feature = "serde")]
+    pub fn from_data<T: serde::de::DeserializeOwned, D: serde::Serialize + ?Sized>(
+        data: &D,
+    ) -> Result<T, TemplateError> {
+        let mut value = serde_json::to_value(data).map_err(|e| TemplateError::InvalidData(e.to_string()))?;
+        let serde_json::Value::Object(fields) = &mut value else {
+            return Err(TemplateError::InvalidData("expected a struct or map".to_string()));
+        };
+        let mut dotted = Vec::new();
+        for (name, value) in fields.iter() {
+            dotted_fields(name, value, &mut dotted);
+        }
+        for (name, value) in dotted {
+            fields.entry(name).or_insert(value);
+        }
+        serde_json::from_value(value).map_err(|e| TemplateError::InvalidData(e.to_string()))
+    }
+
+    #[cfg(feature = "serde")]
+    fn dotted_fields(prefix: &str, value: &serde_json::Value, dotted: &mut Vec<(String, serde_json::Value)>) {
+        let children: Vec<(String, &serde_json::Value)> = match value {
+            serde_json::Value::Object(fields) => fields.iter().map(|(name, value)| (name.clone(), value)).collect(),
+            serde_json::Value::Array(items) => items.iter().enumerate().map(|(i, item)| (i.to_string(), item)).collect(),
+            _ => return,
+        };
+        for (name, child) in children {
+            let name = format!("{}.{}", prefix, name);
+            dotted_fields(&name, child, dotted);
+            dotted.push((name, child.clone()));
+        }
+    }
+
     pub fn save_docx<T: DocxTemplate, P: AsRef<Path>>(
         template: &T,
         output_path: P,
+    ) -> Result<(), TemplateError> {
+        save_docx_with(template, output_path, &RenderOptions::default())
+    }
+
+    pub fn save_docx_with<T: DocxTemplate, P: AsRef<Path>>(
+        template: &T,
+        output_path: P,
+        options: &RenderOptions,
     ) -> Result<(), TemplateError> {
         let template_bytes = std::fs::read(template.template_path())?;
-        save_docx_bytes(&template_bytes, output_path.as_ref(), &template.replacements())
+        save_template_bytes(template, &template_bytes, output_path.as_ref(), options)
     }
 
-    pub fn build_docx_bytes(
+    /// Renders `template` from the given template file contents.
+    pub fn build_template_bytes<T: DocxTemplate>(
+        template: &T,
         template_bytes: &[u8],
-        replacements: &[(&str, &str)],
+        options: &RenderOptions,
     ) -> Result<Vec<u8>, TemplateError> {
-        let cursor = Cursor::new(template_bytes);
-        let mut archive = zip::read::ZipArchive::new(cursor)?;
+        render_docx_bytes(template_bytes, &template.replacements(), &template.chart_series(), options)
+    }
+
+    pub fn save_template_bytes<T: DocxTemplate>(
+        template: &T,
+        template_bytes: &[u8],
+        output_path: &Path,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        PreparedTemplate::from_bytes(template_bytes)?.save(template, output_path, options)
+    }
 
-        let mut output_buf = Cursor::new(Vec::new());
-        let mut zip_writer = zip::write::ZipWriter::new(&mut output_buf);
-        let options = zip::write::SimpleFileOptions::default();
+    /// Renders `template` from the given template file contents into `writer`.
+    pub fn write_template_bytes<T: DocxTemplate, W: Write + Seek>(
+        template: &T,
+        template_bytes: &[u8],
+        writer: W,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        PreparedTemplate::from_bytes(template_bytes)?.write_to(template, writer, options)
+    }
 
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
-            let file_name = file.name().to_string();
+    pub fn build_docx_bytes<V: AsRef<str>>(
+        template_bytes: &[u8],
+        replacements: &[(&str, V)],
+    ) -> Result<Vec<u8>, TemplateError> {
+        build_docx_bytes_with(template_bytes, replacements, &RenderOptions::default())
+    }
 
-            let mut contents = Vec::new();
-            file.read_to_end(&mut contents)?;
+    pub fn build_docx_bytes_with<V: AsRef<str>>(
+        template_bytes: &[u8],
+        replacements: &[(&str, V)],
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, TemplateError> {
+        render_docx_bytes(template_bytes, replacements, &[], options)
+    }
 
-            if file_name.ends_with(".xml") || file_name.ends_with(".rels") {
-                let xml = String::from_utf8(contents)?;
-                let replaced = replace_placeholders_in_xml(&xml, replacements);
-                contents = replaced.into_bytes();
-            }
+    fn render_docx_bytes<V: AsRef<str>>(
+        template_bytes: &[u8],
+        replacements: &[(&str, V)],
+        series: &[chart::Series],
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, TemplateError> {
+        PreparedTemplate::from_bytes(template_bytes)?.render_values(replacements, series, options)
+    }
 
-            zip_writer.start_file(&file_name, options)?;
-            zip_writer.write_all(&contents)?;
-        }
+    /// Holds a generated struct's [`PreparedTemplate`] in embed mode, or the
+    /// error preparing it failed with.
+    pub type PreparedCell = std::sync::OnceLock<Result<PreparedTemplate, TemplateError>>;
 
-        zip_writer.finish()?;
-        Ok(output_buf.into_inner())
+    /// The text of a template part and the placeholders in it, located by
+    /// `generate_templates!` at compile time.
+    pub struct PrecomputedPart {
+        pub name: &'static str,
+        pub spans: &'static [PrecomputedSpan],
+        pub edits: &'static [PrecomputedEdit],
     }
 
-    pub fn save_docx_bytes(
+    /// One text element of a [`PrecomputedPart`], see `LocatedText`.
+    pub struct PrecomputedSpan {
+        /// Index into `TEXT_ELEMENTS`.
+        pub kind: u8,
+        pub start: usize,
+        pub end: usize,
+        pub text: &'static str,
+        pub raw: bool,
+    }
+
+    /// A placeholder matched in the text of a [`PrecomputedSpan`].
+    pub struct PrecomputedEdit {
+        /// Index into the part's `spans`.
+        pub span: usize,
+        /// Byte range of the span's decoded text to replace.
+        pub from: usize,
+        pub to: usize,
+        /// Index into the placeholders passed to [`prepared`] whose value
+        /// replaces the range, or `None` to empty it.
+        pub placeholder: Option<usize>,
+    }
+
+    /// Prepares the embedded template on first use, taking the location of its
+    /// text and of `placeholders` in it from `precomputed` instead of scanning
+    /// the parts again.
+    pub fn prepared(
+        cell: &'static PreparedCell,
+        template_bytes: &'static [u8],
+        placeholders: &'static [&'static str],
+        precomputed: &[PrecomputedPart],
+    ) -> Result<&'static PreparedTemplate, TemplateError> {
+        cell.get_or_init(|| {
+            PreparedTemplate::from_bytes_with(Cow::Borrowed(template_bytes), placeholders, precomputed)
+        })
+        .as_ref()
+        .map_err(TemplateError::duplicate)
+    }
+
+    pub fn save_docx_bytes<V: AsRef<str>>(
+        template_bytes: &[u8],
+        output_path: &Path,
+        replacements: &[(&str, V)],
+    ) -> Result<(), TemplateError> {
+        save_docx_bytes_with(template_bytes, output_path, replacements, &RenderOptions::default())
+    }
+
+    pub fn save_docx_bytes_with<V: AsRef<str>>(
         template_bytes: &[u8],
         output_path: &Path,
-        replacements: &[(&str, &str)],
+        replacements: &[(&str, V)],
+        options: &RenderOptions,
     ) -> Result<(), TemplateError> {
-        let bytes = build_docx_bytes(template_bytes, replacements)?;
+        let prepared = PreparedTemplate::from_bytes(template_bytes)?;
+        write_output(output_path, |writer| prepared.render_values_to(writer, replacements, &[], options))
+    }
+
+    /// Writes the output file with `write`, creating the directories leading up
+    /// to it. The document goes to a temporary file next to it, renamed into
+    /// place once complete, so a failed render neither leaves a partial file
+    /// behind nor replaces an existing one.
+    pub(crate) fn write_output(
+        output_path: &Path,
+        write: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> Result<(), TemplateError>,
+    ) -> Result<(), TemplateError> {
+        static NEXT_TEMP: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
         if let Some(parent) = output_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        std::fs::write(output_path, bytes)?;
-        Ok(())
+        let Some(file_name) = output_path.file_name() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "output path has no file name").into());
+        };
+        let temp_name = format!(
+            ".{}.{}-{}.tmp",
+            file_name.to_string_lossy(),
+            std::process::id(),
+            NEXT_TEMP.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        );
+        let temp_path = output_path.with_file_name(temp_name);
+
+        let result = std::fs::File::create(&temp_path).map_err(TemplateError::from).and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            write(&mut writer)?;
+            writer.into_inner().map_err(|e| e.into_error())?;
+            Ok(std::fs::rename(&temp_path, output_path)?)
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
     }
 }
 
+fn read_entry<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String, TemplateError> {
+    let mut contents = String::new();
+    archive.by_name(name)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
 }
 
-fn replace_placeholders_in_xml(xml: &str, replacements: &[(&str, &str)]) -> String {
-    let mut text_spans: Vec<(usize, usize, String)> = Vec::new();
-    let mut search_start = 0;
-    while let Some(tag_start) = xml[search_start..].find("<w:t") {
-        let tag_start = search_start + tag_start;
-        let content_start = match xml[tag_start..].find('>') {
-            Some(pos) => tag_start + pos + 1,
-            None => break,
+fn escape_xml_attr(s: &str) -> String {
+    escape_xml(s).replace('"', "&quot;").replace('\'', "&apos;")
+}
+
+/// Replaces placeholders in the text content of every element, each text node
+/// on its own. Used for parts such as `docProps/core.xml` that hold plain
+/// element values rather than `<w:t>` runs.
+///
+/// Placeholders are matched in the unescaped text, so `{R&D lead}` written as
+/// `{R&amp;D lead}` is found. Text nodes without a placeholder are kept as
+/// written; the others are escaped again.
+fn replace_placeholders_in_text_nodes(xml: &str, replacements: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(tag_start) = rest.find('<') {
+        let raw = &rest[..tag_start];
+        let text = unescape_text(raw);
+        let matches = find_matches(&text, replacements);
+        if matches.is_empty() {
+            result.push_str(raw);
+        } else {
+            let mut last = 0;
+            for (start, end, value) in matches {
+                result.push_str(&escape_xml(&text[last..start]));
+                result.push_str(&escape_xml(value));
+                last = end;
+            }
+            result.push_str(&escape_xml(&text[last..]));
+        }
+
+        let Some(tag_len) = rest[tag_start..].find('>') else {
+            result.push_str(&rest[tag_start..]);
+            return result;
         };
-        let content_end = match xml[content_start..].find("</w:t>") {
-            Some(pos) => content_start + pos,
-            None => break,
+        result.push_str(&rest[tag_start..tag_start + tag_len + 1]);
+        rest = &rest[tag_start + tag_len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The text nodes of `xml`, unescaped, matching what
+/// [`replace_placeholders_in_text_nodes`] replaces placeholders in.
+fn text_nodes(xml: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    xml.split('<').filter_map(|piece| piece.split_once('>')).map(|(_, text)| unescape_text(text))
+}
+
+/// `text` with XML entities resolved, or as written if it has malformed ones.
+fn unescape_text(text: &str) -> Cow<'_, str> {
+    quick_xml::escape::unescape(text).unwrap_or(Cow::Borrowed(text))
+}
+
+#[cfg(test)]
+fn replace_placeholders_in_xml(xml: &str, replacements: &[(&str, &str)]) -> String {
+    replace_placeholders_in_located(xml, &LocatedText::locate(xml), replacements)
+}
+
+/// Replaces placeholders in hyperlink and other targets of a `.rels` part.
+///
+/// Word percent-encodes braces typed into a hyperlink address, so `%7B`/`%7D`
+/// are decoded before matching; only the placeholders matched are decoded and
+/// replaced, any other encoded brace is kept. Values are percent-encoded like
+/// JavaScript's `encodeURI`: characters that may appear in a URL are kept, so a
+/// whole URL can be filled in, and others such as spaces are encoded.
+fn replace_placeholders_in_relationships(xml: &str, replacements: &[(&str, &str)]) -> String {
+    map_attributes(xml, "Relationship", &["Target"], |raw| {
+        let (decoded, offsets) = decode_percent_braces(raw);
+        let matches = find_matches(&decoded, replacements);
+        if matches.is_empty() {
+            return None;
+        }
+        let mut value = String::new();
+        let mut last = 0;
+        for (start, end, replacement) in matches {
+            value.push_str(&raw[last..offsets[start]]);
+            value.push_str(&escape_xml_attr(&encode_uri(replacement)));
+            last = offsets[end];
+        }
+        value.push_str(&raw[last..]);
+        Some(value)
+    })
+}
+
+/// Percent-encodes the UTF-8 bytes of `value` that can't appear in a URL as is,
+/// like JavaScript's `encodeURI`.
+fn encode_uri(value: &str) -> Cow<'_, str> {
+    let kept = |b: u8| b.is_ascii_alphanumeric() || b"-_.!~*'();/?:@&=+$,#".contains(&b);
+    if value.bytes().all(kept) {
+        return Cow::Borrowed(value);
+    }
+    let mut encoded = String::with_capacity(value.len() * 3);
+    for b in value.bytes() {
+        if kept(b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+/// Replaces placeholders in the given attributes of every `element` start tag.
+/// Placeholders in attribute values are matched whole, never across attributes.
+fn replace_placeholders_in_attributes(
+    xml: &str,
+    element: &str,
+    attributes: &[&str],
+    replacements: &[(&str, &str)],
+) -> String {
+    map_attributes(xml, element, attributes, |raw| {
+        let matches = find_matches(raw, replacements);
+        if matches.is_empty() {
+            return None;
+        }
+        let mut value = String::new();
+        let mut last = 0;
+        for (start, end, replacement) in matches {
+            value.push_str(&raw[last..start]);
+            value.push_str(&escape_xml_attr(replacement));
+            last = end;
+        }
+        value.push_str(&raw[last..]);
+        Some(value)
+    })
+}
+
+/// The values of the given attributes of every `element` start tag, as written.
+fn attribute_values(xml: &str, element: &str, attributes: &[&str]) -> Vec<String> {
+    let mut values = Vec::new();
+    map_attributes(xml, element, attributes, |raw| {
+        values.push(raw.to_string());
+        None
+    });
+    values
+}
+
+/// The targets of every relationship in a `.rels` part, with percent-encoded
+/// braces decoded.
+fn relationship_targets(xml: &str) -> Vec<String> {
+    let targets = attribute_values(xml, "Relationship", &["Target"]);
+    targets.iter().map(|target| decode_percent_braces(target).0).collect()
+}
+
+/// Passes the given attributes of every `element` start tag to `map`, as
+/// written, and writes back the values it returns. Values may be quoted with
+/// `"` or `'`.
+fn map_attributes(
+    xml: &str,
+    element: &str,
+    attributes: &[&str],
+    mut map: impl FnMut(&str) -> Option<String>,
+) -> String {
+    let open = format!("<{}", element);
+    let mut result = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(found) = rest.find(&open) {
+        let after_name = found + open.len();
+        let Some(tag_len) = rest[after_name..].find('>') else {
+            break;
         };
-        let text = xml[content_start..content_end].to_string();
-        text_spans.push((content_start, content_end, text));
-        search_start = content_end + 6;
+        let tag_end = after_name + tag_len;
+        result.push_str(&rest[..after_name]);
+        if !rest[after_name..].starts_with([' ', '\t', '\r', '\n', '/', '>']) {
+            rest = &rest[after_name..];
+            continue;
+        }
+
+        let mut tag = rest[after_name..tag_end].to_string();
+        for attribute in attributes {
+            let key = format!(" {}=", attribute);
+            let Some(key_pos) = tag.find(&key) else {
+                continue;
+            };
+            let Some(quote) = tag[key_pos + key.len()..].chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+                continue;
+            };
+            let value_start = key_pos + key.len() + 1;
+            let Some(value_len) = tag[value_start..].find(quote) else {
+                continue;
+            };
+            let raw = &tag[value_start..value_start + value_len];
+            if let Some(value) = map(raw) {
+                tag.replace_range(value_start..value_start + value_len, &value);
+            }
+        }
+        result.push_str(&tag);
+        rest = &rest[tag_end..];
     }
+    result.push_str(rest);
+    result
+}
+
+/// The located text of a WordprocessingML part, one stream of spans per
+/// element in `TEXT_ELEMENTS`, so placeholders can be replaced any number of
+/// times without parsing the part again.
+pub(crate) struct LocatedText {
+    streams: Vec<Vec<TextSpan>>,
+    /// Placeholders matched at compile time, used while the same ones are
+    /// replaced.
+    matched: Option<MatchedText>,
+}
+
+/// The placeholders of a generated struct matched in a part at compile time.
+struct MatchedText {
+    placeholders: &'static [&'static str],
+    /// Edits by `(stream, span)`, in document order.
+    edits: Vec<((usize, usize), MatchedEdits)>,
+}
 
-    if text_spans.is_empty() {
-        return xml.to_string();
+/// Edits to one span as `(from, to, placeholder)` over its decoded text, with
+/// the placeholder an index into `MatchedText::placeholders`.
+type MatchedEdits = Vec<(usize, usize, Option<usize>)>;
+
+/// Edits to one span as `(from, to, value)` over its decoded text.
+type SpanValues<'s, 'v> = (&'s TextSpan, Vec<(usize, usize, &'v str)>);
+
+impl LocatedText {
+    /// Finds the content of every text element, whatever prefix the part binds
+    /// the WordprocessingML namespace to.
+    pub(crate) fn locate(xml: &str) -> Self {
+        Self { streams: docxide_template_core::locate_text(xml), matched: None }
     }
 
-    let concatenated: String = text_spans.iter().map(|(_, _, t)| t.as_str()).collect();
+    /// The joined text of each stream, as placeholders are matched against it.
+    pub(crate) fn joined(&self) -> impl Iterator<Item = String> + '_ {
+        self.streams.iter().map(|spans| spans.iter().map(|span| span.text.as_str()).collect())
+    }
 
-    let offset_map: Vec<(usize, usize)> = text_spans
-        .iter()
-        .enumerate()
-        .flat_map(|(span_idx, (_, _, text))| {
-            (0..text.len()).map(move |char_offset| (span_idx, char_offset))
-        })
-        .collect();
-
-    let mut span_replacements: Vec<Vec<(usize, usize, String)>> = vec![Vec::new(); text_spans.len()];
-    for &(placeholder, value) in replacements {
-        let mut start = 0;
-        while let Some(found) = concatenated[start..].find(placeholder) {
-            let match_start = start + found;
-            let match_end = match_start + placeholder.len();
-            if match_start >= offset_map.len() || match_end > offset_map.len() {
-                break;
+    /// Takes the text and the matches of `placeholders` located at compile
+    /// time, or `None` if they don't fit `xml`.
+    pub(crate) fn from_precomputed(
+        xml: &str,
+        part: &__private::PrecomputedPart,
+        placeholders: &'static [&'static str],
+    ) -> Option<Self> {
+        let mut streams: Vec<Vec<TextSpan>> = TEXT_ELEMENTS.iter().map(|_| Vec::new()).collect();
+        // Each span's position as `(stream, index)`
+        let mut positions = Vec::with_capacity(part.spans.len());
+        let mut last_end = 0;
+        for span in part.spans {
+            let fits = span.start >= last_end
+                && span.start <= span.end
+                && span.end <= xml.len()
+                && xml.is_char_boundary(span.start)
+                && xml.is_char_boundary(span.end);
+            if !fits {
+                return None;
             }
+            last_end = span.end;
+            let stream = streams.get_mut(span.kind as usize)?;
+            positions.push((span.kind as usize, stream.len()));
+            stream.push(TextSpan { start: span.start, end: span.end, text: span.text.to_string(), raw: span.raw });
+        }
 
-            let (start_span, start_off) = offset_map[match_start];
-            let (end_span, _) = offset_map[match_end - 1];
-            let end_off_exclusive = offset_map[match_end - 1].1 + 1;
-
-            if start_span == end_span {
-                span_replacements[start_span].push((start_off, end_off_exclusive, escape_xml(value)));
-            } else {
-                let first_span_text = &text_spans[start_span].2;
-                span_replacements[start_span].push((start_off, first_span_text.len(), escape_xml(value)));
-                for mid in (start_span + 1)..end_span {
-                    let mid_len = text_spans[mid].2.len();
-                    span_replacements[mid].push((0, mid_len, String::new()));
-                }
-                span_replacements[end_span].push((0, end_off_exclusive, String::new()));
+        let mut edits: Vec<((usize, usize), MatchedEdits)> = Vec::new();
+        for edit in part.edits {
+            let &(stream, index) = positions.get(edit.span)?;
+            let text = &streams[stream][index].text;
+            let fits = edit.from <= edit.to
+                && edit.to <= text.len()
+                && text.is_char_boundary(edit.from)
+                && text.is_char_boundary(edit.to)
+                && edit.placeholder.is_none_or(|i| i < placeholders.len());
+            if !fits {
+                return None;
+            }
+            match edits.iter_mut().find(|(position, _)| *position == (stream, index)) {
+                Some((_, span_edits)) => span_edits.push((edit.from, edit.to, edit.placeholder)),
+                None => edits.push(((stream, index), vec![(edit.from, edit.to, edit.placeholder)])),
             }
-            start = match_end;
         }
+        edits.sort_by_key(|&((stream, index), _)| streams[stream][index].start);
+        for (_, span_edits) in &mut edits {
+            span_edits.sort_by_key(|&(from, _, _)| from);
+        }
+        Some(Self { streams, matched: Some(MatchedText { placeholders, edits }) })
     }
 
-    let mut result = xml.to_string();
-    for (span_idx, (content_start, content_end, _)) in text_spans.iter().enumerate().rev() {
-        let mut span_text = result[*content_start..*content_end].to_string();
-        let mut reps = span_replacements[span_idx].clone();
-        reps.sort_by(|a, b| b.0.cmp(&a.0));
-        for (from, to, replacement) in reps {
-            let safe_to = to.min(span_text.len());
-            span_text = format!("{}{}{}", &span_text[..from], replacement, &span_text[safe_to..]);
+    /// The edits replacing `replacements` makes to the spans, in document
+    /// order. Matches from compile time are used if they were made for the same
+    /// placeholders, in the same order.
+    fn edits<'v>(&self, replacements: &[(&str, &'v str)]) -> Vec<SpanValues<'_, 'v>> {
+        let value = |value: Option<&'v str>| value.unwrap_or("");
+        if let Some(matched) = &self.matched {
+            let same = matched.placeholders.len() == replacements.len()
+                && matched.placeholders.iter().zip(replacements).all(|(p, (r, _))| p == r);
+            if same {
+                return matched
+                    .edits
+                    .iter()
+                    .map(|&((stream, index), ref edits)| {
+                        let edits = edits.iter().map(|&(from, to, p)| (from, to, value(p.map(|i| replacements[i].1))));
+                        (&self.streams[stream][index], edits.collect())
+                    })
+                    .collect();
+            }
         }
-        result = format!("{}{}{}", &result[..*content_start], span_text, &result[*content_end..]);
+
+        let mut edited: Vec<SpanValues> = self
+            .streams
+            .iter()
+            .flat_map(|spans| {
+                span_edits(spans, replacements).into_iter().map(move |(index, edits)| {
+                    (&spans[index], edits.into_iter().map(|(from, to, v)| (from, to, value(v))).collect())
+                })
+            })
+            .collect();
+        edited.sort_by_key(|(span, _)| span.start);
+        edited
     }
+}
+
+/// Replaces placeholders in a part whose text was located up front, writing
+/// the output in one pass. Text that holds no placeholder is left as is.
+fn replace_placeholders_in_located(xml: &str, located: &LocatedText, replacements: &[(&str, &str)]) -> String {
+    let result = replace_placeholders_in_spans(xml, located, replacements);
+    replace_placeholders_in_image_descriptions(&result, replacements)
+}
 
+/// Replaces placeholders in the located text elements of a part only.
+fn replace_placeholders_in_spans(xml: &str, located: &LocatedText, replacements: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(xml.len());
+    let mut last = 0;
+    for (span, edits) in located.edits(replacements) {
+        let keep = |text: &str| if span.raw { text.to_string() } else { escape_xml(text) };
+        result.push_str(&xml[last..span.start]);
+        let mut pos = 0;
+        for (from, to, value) in edits {
+            result.push_str(&keep(&span.text[pos..from]));
+            result.push_str(&escape_xml(value));
+            pos = to;
+        }
+        result.push_str(&keep(&span.text[pos..]));
+        last = span.end;
+    }
+    result.push_str(&xml[last..]);
     result
 }
 
+/// Replaces placeholders in the alt text of images and other drawings.
+fn replace_placeholders_in_image_descriptions(xml: &str, replacements: &[(&str, &str)]) -> String {
+    replace_placeholders_in_attributes(xml, "wp:docPr", &["descr", "title"], replacements)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Cursor;
 
     #[test]
     fn replace_single_run_placeholder() {
@@ -382,6 +930,168 @@ mod tests {
         );
     }
 
+    #[test]
+    fn escaped_brace_is_not_a_placeholder() {
+        let xml = r#"<w:t>\{Name} is {Name}</w:t>"#;
+        let result = replace_placeholders_in_xml(
+            xml,
+            &[("{Name}", "Alice"), ("\\{", "{")],
+        );
+        assert_eq!(result, r#"<w:t>{Name} is Alice</w:t>"#);
+    }
+
+    #[test]
+    fn escape_split_across_runs() {
+        let xml = r#"<w:t>a \</w:t><w:t>{b\}</w:t>"#;
+        let result = replace_placeholders_in_xml(xml, &[("\\{", "{"), ("\\}", "}")]);
+        assert_eq!(result, r#"<w:t>a {</w:t><w:t>b}</w:t>"#);
+    }
+
+    #[test]
+    fn replace_in_text_nodes() {
+        let xml = r#"<cp:coreProperties><dc:title>{title}</dc:title><dc:subject>For {client}</dc:subject></cp:coreProperties>"#;
+        let result = replace_placeholders_in_text_nodes(
+            xml,
+            &[("{title}", "Q3 & Q4"), ("{client}", "Acme")],
+        );
+        assert_eq!(
+            result,
+            r#"<cp:coreProperties><dc:title>Q3 &amp; Q4</dc:title><dc:subject>For Acme</dc:subject></cp:coreProperties>"#
+        );
+    }
+
+    #[test]
+    fn replace_in_escaped_text_nodes() {
+        let xml = r#"<cp:coreProperties><dc:title>{R&amp;D lead} &lt;draft&gt;</dc:title><dc:subject>Q&amp;A</dc:subject></cp:coreProperties>"#;
+        let result = replace_placeholders_in_text_nodes(xml, &[("{R&D lead}", "Ada & Co")]);
+        assert_eq!(
+            result,
+            r#"<cp:coreProperties><dc:title>Ada &amp; Co &lt;draft&gt;</dc:title><dc:subject>Q&amp;A</dc:subject></cp:coreProperties>"#
+        );
+        assert!(text_nodes(xml).any(|text| text == "{R&D lead} <draft>"));
+    }
+
+    #[test]
+    fn elements_starting_with_wt_are_not_text() {
+        let xml = concat!(
+            r#"<w:tbl><w:tblPr/><w:tr><w:tc><w:p><w:r><w:tab/><w:t>{Name}</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"#,
+            r#"<w:p><w:r><w:tab/></w:r><w:r><w:t>after</w:t></w:r></w:p>"#,
+        );
+        let result = replace_placeholders_in_xml(xml, &[("{Name}", "Alice")]);
+        assert_eq!(result, xml.replace("{Name}", "Alice"));
+    }
+
+    #[test]
+    fn other_namespace_prefix() {
+        let xml = concat!(
+            r#"<x:document xmlns:x="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+            r#"<x:p><x:r><x:t>{Na</x:t></x:r><x:r><x:t>me}</x:t></x:r></x:p></x:document>"#,
+        );
+        let result = replace_placeholders_in_xml(xml, &[("{Name}", "Alice")]);
+        assert!(result.contains("<x:t>Alice</x:t></x:r><x:r><x:t></x:t>"), "{}", result);
+    }
+
+    #[test]
+    fn foreign_t_elements_are_left_alone() {
+        let xml = r#"<root xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:t>{Name}</a:t><w:t>{Name}</w:t></root>"#;
+        let result = replace_placeholders_in_xml(xml, &[("{Name}", "Alice")]);
+        assert!(result.contains("<a:t>{Name}</a:t><w:t>Alice</w:t>"), "{}", result);
+    }
+
+    #[test]
+    fn entities_are_decoded_before_matching() {
+        let xml = r#"<w:t>{a&amp;b} &quot;kept&quot;</w:t><w:t>R&amp;D</w:t>"#;
+        let result = replace_placeholders_in_xml(xml, &[("{a&b}", "<x>")]);
+        assert_eq!(result, r#"<w:t>&lt;x&gt; "kept"</w:t><w:t>R&amp;D</w:t>"#);
+    }
+
+    #[test]
+    fn precomputed_text_matches_located() {
+        let xml = r#"<w:p><w:r><w:t>{Na</w:t></w:r><w:r><w:instrText>{x}</w:instrText><w:t>me} &amp;</w:t></w:r></w:p>"#;
+        let span = |kind, content: &str, text: &'static str| {
+            let start = xml.find(content).unwrap();
+            __private::PrecomputedSpan { kind, start, end: start + content.len(), text, raw: false }
+        };
+        let edit = |span, from, to, placeholder| __private::PrecomputedEdit { span, from, to, placeholder };
+        let spans = vec![span(0, "{Na", "{Na"), span(1, "{x}", "{x}"), span(0, "me} &amp;", "me} &")].leak();
+        let edits = vec![edit(0, 0, 3, Some(0)), edit(2, 0, 3, None), edit(1, 0, 3, Some(1))].leak();
+        let part = __private::PrecomputedPart { name: "word/document.xml", spans, edits };
+        let precomputed = LocatedText::from_precomputed(xml, &part, &["{Name}", "{x}"]).unwrap();
+        let reps = [("{Name}", "Alice"), ("{x}", "y")];
+        let expected = replace_placeholders_in_located(xml, &LocatedText::locate(xml), &reps);
+        assert_eq!(replace_placeholders_in_located(xml, &precomputed, &reps), expected);
+        // Other placeholders are matched when rendering
+        let other = [("{x}", "y"), ("{Name}", "Alice")];
+        assert_eq!(replace_placeholders_in_located(xml, &precomputed, &other), expected);
+
+        let out_of_bounds = vec![__private::PrecomputedSpan { kind: 0, start: 15, end: 500, text: "", raw: false }].leak();
+        let part = __private::PrecomputedPart { name: "word/document.xml", spans: out_of_bounds, edits: &[] };
+        assert!(LocatedText::from_precomputed(xml, &part, &[]).is_none());
+        let part = __private::PrecomputedPart { name: "word/document.xml", spans, edits: vec![edit(0, 0, 9, Some(0))].leak() };
+        assert!(LocatedText::from_precomputed(xml, &part, &["{Name}"]).is_none());
+    }
+
+    #[test]
+    fn replace_in_field_instructions() {
+        let xml = concat!(
+            r#"<w:r><w:instrText xml:space="preserve"> HYPERLINK "{ur</w:instrText></w:r>"#,
+            r#"<w:r><w:instrText>l}" </w:instrText></w:r><w:r><w:t>{label}</w:t></w:r>"#,
+        );
+        let result = replace_placeholders_in_xml(
+            xml,
+            &[("{url}", "https://example.com/?a=1&b=2"), ("{label}", "Docs")],
+        );
+        assert_eq!(
+            result,
+            concat!(
+                r#"<w:r><w:instrText xml:space="preserve"> HYPERLINK "https://example.com/?a=1&amp;b=2</w:instrText></w:r>"#,
+                r#"<w:r><w:instrText>" </w:instrText></w:r><w:r><w:t>Docs</w:t></w:r>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn replace_in_image_alt_text() {
+        let xml = r#"<wp:docPr id="1" name="Picture {n}" descr="Photo of {person}" title="{title}"/>"#;
+        let result = replace_placeholders_in_xml(
+            xml,
+            &[("{person}", r#"Ada "the Countess""#), ("{title}", "Portrait"), ("{n}", "X")],
+        );
+        assert_eq!(
+            result,
+            r#"<wp:docPr id="1" name="Picture {n}" descr="Photo of Ada &quot;the Countess&quot;" title="Portrait"/>"#
+        );
+    }
+
+    #[test]
+    fn replace_in_relationship_targets() {
+        let xml = concat!(
+            r#"<Relationships>"#,
+            r#"<Relationship Id="rId1" Type="hyperlink" Target="https://example.com/users/{id}" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId2" Type="hyperlink" Target="https://example.com/%7Bslug%7D" TargetMode="External"/>"#,
+            r#"<Relationship Id="rId3" Type="hyperlink" Target="https://example.com/%7Bother%7D" TargetMode="External"/>"#,
+            r#"</Relationships>"#,
+        );
+        let result = replace_placeholders_in_relationships(xml, &[("{id}", "42"), ("{slug}", "a&b")]);
+        assert!(result.contains(r#"Target="https://example.com/users/42""#), "{}", result);
+        assert!(result.contains(r#"Target="https://example.com/a&amp;b""#), "{}", result);
+        assert!(result.contains(r#"Target="https://example.com/%7Bother%7D""#), "{}", result);
+    }
+
+    #[test]
+    fn relationship_targets_decode_and_encode_only_placeholders() {
+        let xml = concat!(
+            r#"<Relationship Id="rId1" Target="https://example.com/%7Bpath%7D?q=%7Bother%7D" TargetMode="External"/>"#,
+            r#"<Relationship Id='rId2' Target='mailto:{email}?subject={subject}' TargetMode='External'/>"#,
+        );
+        let result = replace_placeholders_in_relationships(
+            xml,
+            &[("{path}", "a b/ø"), ("{email}", "ada@example.com"), ("{subject}", "Ada's <report>")],
+        );
+        assert!(result.contains(r#"Target="https://example.com/a%20b/%C3%B8?q=%7Bother%7D""#), "{}", result);
+        assert!(result.contains(r#"Target='mailto:ada@example.com?subject=Ada&apos;s%20%3Creport%3E'"#), "{}", result);
+    }
+
     #[test]
     fn replace_headfoottest_template() {
         let template_path = Path::new("../test-crate/templates/HeadFootTest.docx");
@@ -417,6 +1127,32 @@ mod tests {
     }
 
     #[test]
+    fn failed_write_keeps_existing_output() {
+        let dir = std::env::temp_dir().join(format!("docxide-write-output-{}", std::process::id()));
+        let path = dir.join("out.docx");
+        __private::write_output(&path, |writer| Ok(writer.write_all(b"first")?)).unwrap();
+
+        let result = __private::write_output(&path, |writer| {
+            writer.write_all(b"partial")?;
+            Err(TemplateError::InvalidData("failed".to_string()))
+        });
+        assert!(matches!(result, Err(TemplateError::InvalidData(_))));
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1, "temporary file left behind");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn prepared_cell_keeps_error_variant() {
+        static CELL: __private::PreparedCell = __private::PreparedCell::new();
+        for _ in 0..2 {
+            let result = __private::prepared(&CELL, b"not a zip", &[], &[]);
+            assert!(matches!(result, Err(TemplateError::InvalidTemplate(_))));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::len_zero)]
     fn build_docx_bytes_produces_valid_zip() {
         let template_path = Path::new("../test-crate/templates/HelloWorld.docx");
         if !template_path.exists() {
@@ -435,6 +1171,68 @@ mod tests {
         assert!(archive.len() > 0);
     }
 
+    #[test]
+    fn build_docx_bytes_copies_unchanged_entries_as_is() {
+        let template_path = Path::new("../test-crate/templates/HelloWorld.docx");
+        if !template_path.exists() {
+            return;
+        }
+        let template_bytes = std::fs::read(template_path).unwrap();
+        let result = __private::build_docx_bytes(&template_bytes, &[("{ firstName }", "Test")]).unwrap();
+
+        let mut template = zip::ZipArchive::new(Cursor::new(&template_bytes)).unwrap();
+        let mut output = zip::ZipArchive::new(Cursor::new(&result)).unwrap();
+        let original = template.by_name("word/styles.xml").unwrap();
+        let copied = output.by_name("word/styles.xml").unwrap();
+        assert_eq!(copied.compression(), original.compression());
+        assert_eq!(copied.compressed_size(), original.compressed_size());
+        assert_eq!(copied.crc32(), original.crc32());
+        assert_eq!(copied.last_modified(), original.last_modified());
+    }
+
+    #[test]
+    fn build_docx_bytes_keeps_compression_and_time_of_changed_entries() {
+        let template_path = Path::new("../test-crate/templates/HelloWorld.docx");
+        if !template_path.exists() {
+            return;
+        }
+        let template_bytes = std::fs::read(template_path).unwrap();
+        let result = __private::build_docx_bytes(&template_bytes, &[("{ firstName }", "Test")]).unwrap();
+
+        let mut template = zip::ZipArchive::new(Cursor::new(&template_bytes)).unwrap();
+        let mut output = zip::ZipArchive::new(Cursor::new(&result)).unwrap();
+        let original = template.by_name("word/document.xml").unwrap();
+        let rendered = output.by_name("word/document.xml").unwrap();
+        assert_ne!(rendered.crc32(), original.crc32());
+        assert_eq!(rendered.compression(), original.compression());
+        assert_eq!(rendered.last_modified(), original.last_modified());
+    }
+
+    #[test]
+    fn deterministic_output_is_byte_identical() {
+        let template_path = Path::new("../test-crate/templates/HelloWorld.docx");
+        if !template_path.exists() {
+            return;
+        }
+        let template_bytes = std::fs::read(template_path).unwrap();
+        let options = RenderOptions::new().deterministic(true).custom_property("Ref", "42");
+        let render = || {
+            __private::build_docx_bytes_with(&template_bytes, &[("{ firstName }", "Test")], &options).unwrap()
+        };
+        let result = render();
+        assert_eq!(result, render());
+
+        let template = zip::ZipArchive::new(Cursor::new(&template_bytes)).unwrap();
+        let mut output = zip::ZipArchive::new(Cursor::new(&result)).unwrap();
+        let names: Vec<_> = output.file_names().map(str::to_string).collect();
+        let template_names: Vec<_> = template.file_names().collect();
+        assert_eq!(names[..names.len() - 1], template_names[..]);
+        assert_eq!(names.last().map(String::as_str), Some("docProps/custom.xml"));
+        for i in 0..output.len() {
+            assert_eq!(output.by_index_raw(i).unwrap().last_modified(), Some(zip::DateTime::default()));
+        }
+    }
+
     #[test]
     fn escape_xml_special_characters() {
         let xml = r#"<w:t>{Name}</w:t>"#;
@@ -515,4 +1313,68 @@ mod tests {
         assert!(!doc_xml.contains("firstName"));
         assert!(!doc_xml.contains("productName"));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn from_data_offers_nested_fields_under_dotted_names() {
+        #[derive(serde::Deserialize)]
+        struct Letter {
+            #[serde(alias = "customer.name")]
+            customer_name: String,
+            #[serde(alias = "lines.1")]
+            second_line: String,
+        }
+
+        let data = serde_json::json!({ "customer": { "name": "Acme" }, "lines": ["a", "b"] });
+        let letter: Letter = __private::from_data(&data).unwrap();
+        assert_eq!(letter.customer_name, "Acme");
+        assert_eq!(letter.second_line, "b");
+        assert!(matches!(__private::from_data::<Letter, _>(&[1]), Err(TemplateError::InvalidData(_))));
+    }
+
+    enum Status {
+        Paid,
+        Due,
+    }
+
+    impl __private::Sealed for Status {}
+
+    impl std::fmt::Display for Status {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(match self {
+                Status::Paid => "Paid",
+                Status::Due => "Due",
+            })
+        }
+    }
+
+    impl DropdownValue for Status {
+        const ENTRIES: &'static [&'static str] = &["Paid", "Due"];
+    }
+
+    #[test]
+    fn dropdowns_take_types_with_listed_entries() {
+        const _: () = __private::check_dropdown::<Status>(&["Due", "Paid", "Cancelled"]);
+        assert_eq!([Status::Paid, Status::Due].map(|status| status.to_string()), ["Paid", "Due"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing an entry")]
+    fn dropdowns_reject_types_with_other_entries() {
+        __private::check_dropdown::<Status>(&["Paid"]);
+    }
 }
+
+/// `collisions = error` fails to compile for a template whose placeholder
+/// names are spelled differently but make the same field, which `merge`
+/// accepts:
+///
+/// ```compile_fail
+/// docxide_template::generate_templates!("test-crate/templates", collisions = error);
+/// ```
+///
+/// ```
+/// docxide_template::generate_templates!("test-crate/templates", collisions = merge);
+/// ```
+#[cfg(doctest)]
+struct CollisionsError;
@@ -0,0 +1,78 @@
+/// Settings applied when rendering a template, passed to the generated
+/// `save_with()` and `to_bytes_with()` methods.
+///
+/// ```rust,ignore
+/// let options = RenderOptions::new()
+///     .title("Invoice 1042")
+///     .last_modified_by("Billing")
+///     .custom_property("InvoiceId", "1042");
+/// invoice.save_with("output/invoice", &options)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    pub(crate) core_properties: Vec<(String, String)>,
+    pub(crate) custom_properties: Vec<(String, String)>,
+    pub(crate) deterministic: bool,
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a core document property by its qualified element name in
+    /// `docProps/core.xml`, e.g. `dc:title`, `cp:lastModifiedBy` or `cp:contentStatus`.
+    pub fn core_property(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.core_properties.retain(|(n, _)| *n != name);
+        self.core_properties.push((name, value.into()));
+        self
+    }
+
+    /// Sets a custom document property (File > Info > Properties > Custom in Word).
+    pub fn custom_property(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.custom_properties.retain(|(n, _)| *n != name);
+        self.custom_properties.push((name, value.into()));
+        self
+    }
+
+    /// Stamps every entry of the output with the same fixed time (1980-01-01,
+    /// the earliest a zip archive can record), so rendering the same values
+    /// produces byte-identical documents.
+    ///
+    /// Otherwise entries keep the template's timestamps, and entries the template
+    /// does not have, like an added custom properties part, get the current time.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    pub fn title(self, value: impl Into<String>) -> Self {
+        self.core_property("dc:title", value)
+    }
+
+    pub fn subject(self, value: impl Into<String>) -> Self {
+        self.core_property("dc:subject", value)
+    }
+
+    pub fn creator(self, value: impl Into<String>) -> Self {
+        self.core_property("dc:creator", value)
+    }
+
+    pub fn keywords(self, value: impl Into<String>) -> Self {
+        self.core_property("cp:keywords", value)
+    }
+
+    pub fn description(self, value: impl Into<String>) -> Self {
+        self.core_property("dc:description", value)
+    }
+
+    pub fn category(self, value: impl Into<String>) -> Self {
+        self.core_property("cp:category", value)
+    }
+
+    pub fn last_modified_by(self, value: impl Into<String>) -> Self {
+        self.core_property("cp:lastModifiedBy", value)
+    }
+}
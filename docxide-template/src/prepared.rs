@@ -0,0 +1,359 @@
+//! Templates parsed once and rendered any number of times.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+
+use crate::__private::PrecomputedPart;
+use crate::{
+    attribute_values, chart, properties, relationship_targets, replace_placeholders_in_located,
+    replace_placeholders_in_relationships, replace_placeholders_in_text_nodes, text_nodes, DocxTemplate, LocatedText, RenderOptions, TemplateError,
+};
+
+/// A `.docx` template that has been decompressed and scanned for text once, so
+/// rendering it again only substitutes values.
+///
+/// Generated structs keep one per template when the `embed` feature is enabled.
+/// Without it, hold on to one yourself when rendering the same template many times:
+///
+/// ```rust,ignore
+/// let prepared = PreparedTemplate::from_path("templates/Letter.docx")?;
+/// for letter in letters {
+///     let bytes = prepared.render(&letter, &RenderOptions::default())?;
+/// }
+/// ```
+pub struct PreparedTemplate {
+    /// The template archive, from which unchanged entries are copied as-is.
+    template_bytes: Cow<'static, [u8]>,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    name: String,
+    /// Index of the entry in the template archive.
+    index: usize,
+    contents: Contents,
+}
+
+enum Contents {
+    /// A WordprocessingML part with its text located.
+    Document { xml: String, text: LocatedText },
+    /// Any other XML part: relationships, document properties, charts, ...
+    Xml(String),
+    /// Media, fonts, embedded workbooks and the like, kept compressed.
+    Binary,
+}
+
+impl PreparedTemplate {
+    pub fn from_bytes(template_bytes: &[u8]) -> Result<Self, TemplateError> {
+        Self::from_bytes_with(Cow::Owned(template_bytes.to_vec()), &[], &[])
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), taking the text of the parts in
+    /// `precomputed` and the matches of `placeholders` in it as located at
+    /// compile time.
+    pub(crate) fn from_bytes_with(
+        template_bytes: Cow<'static, [u8]>,
+        placeholders: &'static [&'static str],
+        precomputed: &[PrecomputedPart],
+    ) -> Result<Self, TemplateError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(&*template_bytes))?;
+        let mut entries = Vec::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            let name = file.name().to_string();
+            if !(name.ends_with(".xml") || name.ends_with(".rels")) {
+                entries.push(Entry { name, index, contents: Contents::Binary });
+                continue;
+            }
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
+
+            let contents = if name.ends_with(".rels")
+                || name.starts_with("docProps/")
+                || name == properties::CONTENT_TYPES_PART
+                || chart::is_chart_part(&name)
+            {
+                Contents::Xml(String::from_utf8(contents)?)
+            } else {
+                let xml = String::from_utf8(contents)?;
+                let text = precomputed
+                    .iter()
+                    .find(|part| part.name == name)
+                    .and_then(|part| LocatedText::from_precomputed(&xml, part, placeholders))
+                    .unwrap_or_else(|| LocatedText::locate(&xml));
+                Contents::Document { xml, text }
+            };
+            entries.push(Entry { name, index, contents });
+        }
+        drop(archive);
+        Ok(Self { template_bytes, entries })
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, TemplateError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Renders `template`'s values into this template and returns the `.docx` bytes.
+    pub fn render<T: DocxTemplate>(&self, template: &T, options: &RenderOptions) -> Result<Vec<u8>, TemplateError> {
+        self.render_values(&template.replacements(), &template.chart_series(), options)
+    }
+
+    /// Renders `template`'s values into this template and writes the `.docx` to
+    /// `writer` as it goes, without holding the whole document in memory.
+    pub fn write_to<T: DocxTemplate, W: Write + Seek>(
+        &self,
+        template: &T,
+        writer: W,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        self.render_values_to(writer, &template.replacements(), &template.chart_series(), options)
+    }
+
+    /// Renders `template`'s values into this template and writes the `.docx` to `path`.
+    pub fn save<T: DocxTemplate, P: AsRef<Path>>(
+        &self,
+        template: &T,
+        path: P,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        crate::__private::write_output(path.as_ref(), |writer| self.write_to(template, writer, options))
+    }
+
+    /// Fills the template's `{name}` placeholders from the fields of `data`,
+    /// any value that serializes to a struct or map, expanding sections as
+    /// [`DynamicTemplate::render_with`](crate::DynamicTemplate::render_with)
+    /// does. The placeholders are looked up on every call; to render many
+    /// documents from data, load a `DynamicTemplate`, which finds them once.
+    #[cfg(feature = "serde")]
+    pub fn render_with<T: serde::Serialize + ?Sized>(
+        &self,
+        data: &T,
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, TemplateError> {
+        let mut output = Cursor::new(Vec::new());
+        self.write_with(data, &mut output, options)?;
+        Ok(output.into_inner())
+    }
+
+    /// Fills the template from the fields of `data`, like
+    /// [`render_with`](Self::render_with), and writes the `.docx` to `writer`.
+    #[cfg(feature = "serde")]
+    pub fn write_with<T: serde::Serialize + ?Sized, W: Write + Seek>(
+        &self,
+        data: &T,
+        writer: W,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        let placeholders = crate::dynamic::template_placeholders(self);
+        crate::dynamic::write_data(self, &placeholders, data, writer, options)
+    }
+
+    /// Fills the template from the fields of `data`, like
+    /// [`render_with`](Self::render_with), and writes the `.docx` to `path`.
+    #[cfg(feature = "serde")]
+    pub fn save_with<T: serde::Serialize + ?Sized, P: AsRef<Path>>(
+        &self,
+        data: &T,
+        path: P,
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        crate::__private::write_output(path.as_ref(), |writer| self.write_with(data, writer, options))
+    }
+
+    /// The text placeholders are matched against when rendering: the joined
+    /// text and image descriptions of each document part, document properties
+    /// and relationship targets. Charts are left out.
+    pub(crate) fn searchable_text(&self) -> Vec<String> {
+        let mut texts = Vec::new();
+        for entry in &self.entries {
+            match &entry.contents {
+                Contents::Document { xml, text } => {
+                    texts.extend(text.joined());
+                    texts.extend(attribute_values(xml, "wp:docPr", &["descr", "title"]));
+                }
+                Contents::Xml(xml) if entry.name.starts_with("docProps/") => {
+                    texts.extend(text_nodes(xml).map(Cow::into_owned));
+                }
+                Contents::Xml(xml) if entry.name.ends_with(".rels") => {
+                    texts.extend(relationship_targets(xml));
+                }
+                _ => {}
+            }
+        }
+        texts
+    }
+
+    fn entry(&self, name: &str) -> Option<&Contents> {
+        self.entries.iter().find(|entry| entry.name == name).map(|entry| &entry.contents)
+    }
+
+    pub(crate) fn render_values<V: AsRef<str>>(
+        &self,
+        replacements: &[(&str, V)],
+        series: &[chart::Series],
+        options: &RenderOptions,
+    ) -> Result<Vec<u8>, TemplateError> {
+        let mut output = Cursor::new(Vec::new());
+        self.render_values_to(&mut output, replacements, series, options)?;
+        Ok(output.into_inner())
+    }
+
+    pub(crate) fn render_values_to<V: AsRef<str>, W: Write + Seek>(
+        &self,
+        writer: W,
+        replacements: &[(&str, V)],
+        series: &[chart::Series],
+        options: &RenderOptions,
+    ) -> Result<(), TemplateError> {
+        let replacements: Vec<(&str, &str)> =
+            replacements.iter().map(|(p, v)| (*p, v.as_ref())).collect();
+        self.render_to(writer, &replacements, series, options, |xml, text| {
+            replace_placeholders_in_located(xml, text, &replacements)
+        })
+    }
+
+    /// Writes the rendered template to `writer`, filling WordprocessingML parts
+    /// with `fill_document` and every other part from `replacements`.
+    pub(crate) fn render_to<W: Write + Seek>(
+        &self,
+        writer: W,
+        replacements: &[(&str, &str)],
+        series: &[chart::Series],
+        options: &RenderOptions,
+        fill_document: impl Fn(&str, &LocatedText) -> String,
+    ) -> Result<(), TemplateError> {
+        let add_custom_part =
+            !options.custom_properties.is_empty() && self.entry(properties::CUSTOM_PART).is_none();
+
+        // Charts are filled up front, as the workbooks embedded with them may
+        // come earlier in the archive.
+        let mut charts: HashMap<&str, String> = HashMap::new();
+        let mut workbook_updates: HashMap<String, Vec<chart::CellUpdate>> = HashMap::new();
+        if !series.is_empty() {
+            for entry in &self.entries {
+                let Contents::Xml(xml) = &entry.contents else {
+                    continue;
+                };
+                if !chart::is_chart_part(&entry.name) {
+                    continue;
+                }
+                let (xml, updates) = chart::fill_chart(xml, series);
+                if !updates.is_empty() {
+                    let rels = match self.entry(&chart::rels_part(&entry.name)) {
+                        Some(Contents::Xml(rels)) => rels.as_str(),
+                        _ => "",
+                    };
+                    if let Some(workbook) = chart::embedded_workbook_part(&entry.name, &xml, rels) {
+                        workbook_updates.entry(workbook).or_default().extend(updates);
+                    }
+                }
+                charts.insert(&entry.name, xml);
+            }
+        }
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(&*self.template_bytes))?;
+        let mut zip_writer = zip::write::ZipWriter::new(writer);
+        let fixed_time = options.deterministic.then(zip::DateTime::default);
+
+        for entry in &self.entries {
+            let name = entry.name.as_str();
+            // New contents, or `None` to copy the entry unchanged
+            let contents: Option<Vec<u8>> = match &entry.contents {
+                Contents::Document { xml, text } => {
+                    changed(xml, fill_document(xml, text))
+                }
+                Contents::Binary => match workbook_updates.get(name) {
+                    Some(updates) => {
+                        let mut workbook = Vec::new();
+                        archive.by_index(entry.index)?.read_to_end(&mut workbook)?;
+                        Some(chart::fill_workbook(&workbook, updates, fixed_time)?)
+                    }
+                    None => None,
+                },
+                Contents::Xml(xml) => match name {
+                    properties::CORE_PART => changed(
+                        xml,
+                        properties::apply_core_properties(
+                            &replace_placeholders_in_text_nodes(xml, replacements),
+                            &options.core_properties,
+                        ),
+                    ),
+                    properties::CUSTOM_PART => changed(
+                        xml,
+                        properties::apply_custom_properties(
+                            &replace_placeholders_in_text_nodes(xml, replacements),
+                            &options.custom_properties,
+                        ),
+                    ),
+                    properties::CONTENT_TYPES_PART if add_custom_part => {
+                        changed(xml, properties::register_custom_part_content_type(xml))
+                    }
+                    properties::PACKAGE_RELS_PART if add_custom_part => {
+                        changed(xml, properties::register_custom_part_relationship(xml))
+                    }
+                    name if name.starts_with("docProps/") => {
+                        changed(xml, replace_placeholders_in_text_nodes(xml, replacements))
+                    }
+                    name if name.ends_with(".rels") => {
+                        changed(xml, replace_placeholders_in_relationships(xml, replacements))
+                    }
+                    name => charts.remove(name).and_then(|filled| changed(xml, filled)),
+                },
+            };
+
+            let original = archive.by_index_raw(entry.index)?;
+            match (contents, fixed_time) {
+                (Some(contents), _) => {
+                    let zip_options = entry_options(&original, fixed_time);
+                    drop(original);
+                    zip_writer.start_file(name, zip_options)?;
+                    zip_writer.write_all(&contents)?;
+                }
+                (None, Some(time)) => {
+                    let unix_mode = original.unix_mode();
+                    zip_writer.raw_copy_file_touch(original, time, unix_mode)?;
+                }
+                (None, None) => zip_writer.raw_copy_file(original)?,
+            }
+        }
+
+        if add_custom_part {
+            let zip_options = zip::write::SimpleFileOptions::default()
+                .last_modified_time(fixed_time.unwrap_or_else(zip::DateTime::default_for_write));
+            zip_writer.start_file(properties::CUSTOM_PART, zip_options)?;
+            zip_writer.write_all(
+                properties::new_custom_properties_part(&options.custom_properties).as_bytes(),
+            )?;
+        }
+
+        zip_writer.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Options to write a changed entry with: the compression method and timestamp
+/// of the template's entry, or `fixed_time` in deterministic mode.
+pub(crate) fn entry_options(
+    original: &zip::read::ZipFile,
+    fixed_time: Option<zip::DateTime>,
+) -> zip::write::SimpleFileOptions {
+    // Word only writes stored and deflated entries; anything else is deflated
+    let compression = match original.compression() {
+        zip::CompressionMethod::Stored => zip::CompressionMethod::Stored,
+        _ => zip::CompressionMethod::Deflated,
+    };
+    let mut options = zip::write::SimpleFileOptions::default()
+        .compression_method(compression)
+        .last_modified_time(fixed_time.or_else(|| original.last_modified()).unwrap_or_default());
+    if let Some(mode) = original.unix_mode() {
+        options = options.unix_permissions(mode);
+    }
+    options
+}
+
+/// The rendered part, unless rendering left it unchanged.
+fn changed(original: &str, rendered: String) -> Option<Vec<u8>> {
+    (rendered != original).then(|| rendered.into_bytes())
+}
@@ -0,0 +1,230 @@
+//! Editing of the document property parts (`docProps/core.xml` and `docProps/custom.xml`).
+
+use crate::{escape_xml, escape_xml_attr};
+
+pub(crate) const CORE_PART: &str = "docProps/core.xml";
+pub(crate) const CUSTOM_PART: &str = "docProps/custom.xml";
+pub(crate) const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+pub(crate) const PACKAGE_RELS_PART: &str = "_rels/.rels";
+
+const CUSTOM_PROPERTIES_FMTID: &str = "{D5CDD505-2E9C-101B-9397-08002B2CF9AE}";
+const CUSTOM_PROPERTIES_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.custom-properties+xml";
+const CUSTOM_PROPERTIES_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties";
+
+/// Finds the start tag of element `name`, returning its start offset and the
+/// offset just past its `>`.
+fn find_start_tag(xml: &str, name: &str) -> Option<(usize, usize)> {
+    let open = format!("<{}", name);
+    let mut search_start = 0;
+    while let Some(found) = xml[search_start..].find(&open) {
+        let tag_start = search_start + found;
+        let after_name = tag_start + open.len();
+        if xml[after_name..].starts_with(['>', '/', ' ', '\t', '\r', '\n']) {
+            let tag_end = after_name + xml[after_name..].find('>')? + 1;
+            return Some((tag_start, tag_end));
+        }
+        search_start = after_name;
+    }
+    None
+}
+
+/// Sets the text content of element `name`, inserting the element before
+/// `</root>` if the part doesn't have it yet.
+fn set_element_text(xml: &str, root: &str, name: &str, value: &str) -> String {
+    let value = escape_xml(value);
+    if let Some((tag_start, tag_end)) = find_start_tag(xml, name) {
+        let start_tag = &xml[tag_start..tag_end];
+        if let Some(attrs) = start_tag.strip_suffix("/>") {
+            return format!("{}{}>{}</{}>{}", &xml[..tag_start], attrs, value, name, &xml[tag_end..]);
+        }
+        let close = format!("</{}>", name);
+        if let Some(content_len) = xml[tag_end..].find(&close) {
+            return format!("{}{}{}", &xml[..tag_end], value, &xml[tag_end + content_len..]);
+        }
+    }
+
+    let close_root = format!("</{}>", root);
+    let Some(insert_at) = xml.rfind(&close_root) else {
+        return xml.to_string();
+    };
+    let type_attr = if name.starts_with("dcterms:") { r#" xsi:type="dcterms:W3CDTF""# } else { "" };
+    format!("{}<{}{}>{}</{}>{}", &xml[..insert_at], name, type_attr, value, name, &xml[insert_at..])
+}
+
+pub(crate) fn apply_core_properties(xml: &str, properties: &[(String, String)]) -> String {
+    properties.iter().fold(xml.to_string(), |xml, (name, value)| {
+        set_element_text(&xml, "cp:coreProperties", name, value)
+    })
+}
+
+fn custom_property_element(pid: u32, name: &str, value: &str) -> String {
+    format!(
+        r#"<property fmtid="{}" pid="{}" name="{}"><vt:lpwstr>{}</vt:lpwstr></property>"#,
+        CUSTOM_PROPERTIES_FMTID,
+        pid,
+        escape_xml_attr(name),
+        escape_xml(value)
+    )
+}
+
+/// Sets custom properties, replacing the value of existing properties with the
+/// same name and appending new ones.
+pub(crate) fn apply_custom_properties(xml: &str, properties: &[(String, String)]) -> String {
+    let mut xml = xml.to_string();
+    for (name, value) in properties {
+        let mut max_pid = 1;
+        let mut existing = None;
+        let mut search_start = 0;
+        while let Some(found) = xml[search_start..].find("<property ") {
+            let start = search_start + found;
+            let Some(len) = xml[start..].find("</property>") else {
+                break;
+            };
+            let end = start + len + "</property>".len();
+            let element = &xml[start..end];
+            if let Some(pid) = attribute(element, "pid").and_then(|p| p.parse::<u32>().ok()) {
+                max_pid = max_pid.max(pid);
+            }
+            if attribute(element, "name") == Some(escape_xml_attr(name).as_str()) {
+                let pid = attribute(element, "pid").and_then(|p| p.parse().ok()).unwrap_or(2);
+                existing = Some((start, end, pid));
+            }
+            search_start = end;
+        }
+
+        xml = match existing {
+            Some((start, end, pid)) => format!(
+                "{}{}{}",
+                &xml[..start],
+                custom_property_element(pid, name, value),
+                &xml[end..]
+            ),
+            None => match xml.rfind("</Properties>") {
+                Some(insert_at) => format!(
+                    "{}{}{}",
+                    &xml[..insert_at],
+                    custom_property_element(max_pid + 1, name, value),
+                    &xml[insert_at..]
+                ),
+                None => xml,
+            },
+        };
+    }
+    xml
+}
+
+pub(crate) fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start_tag = &element[..element.find('>')?];
+    let key = format!(" {}=\"", name);
+    let value_start = start_tag.find(&key)? + key.len();
+    let value_len = start_tag[value_start..].find('"')?;
+    Some(&start_tag[value_start..value_start + value_len])
+}
+
+/// A new `docProps/custom.xml` for templates that don't have one.
+pub(crate) fn new_custom_properties_part(properties: &[(String, String)]) -> String {
+    let empty = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        "\n",
+        r#"<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" "#,
+        r#"xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"></Properties>"#,
+    );
+    apply_custom_properties(empty, properties)
+}
+
+/// Registers a newly added `docProps/custom.xml` in `[Content_Types].xml`.
+pub(crate) fn register_custom_part_content_type(xml: &str) -> String {
+    let Some(insert_at) = xml.rfind("</Types>") else {
+        return xml.to_string();
+    };
+    format!(
+        r#"{}<Override PartName="/{}" ContentType="{}"/>{}"#,
+        &xml[..insert_at],
+        CUSTOM_PART,
+        CUSTOM_PROPERTIES_CONTENT_TYPE,
+        &xml[insert_at..]
+    )
+}
+
+/// Registers a newly added `docProps/custom.xml` in the package relationships.
+pub(crate) fn register_custom_part_relationship(xml: &str) -> String {
+    let Some(insert_at) = xml.rfind("</Relationships>") else {
+        return xml.to_string();
+    };
+    let mut id = "rIdCustomProperties".to_string();
+    while xml.contains(&format!("Id=\"{}\"", id)) {
+        id.push('1');
+    }
+    format!(
+        r#"{}<Relationship Id="{}" Type="{}" Target="{}"/>{}"#,
+        &xml[..insert_at],
+        id,
+        CUSTOM_PROPERTIES_REL_TYPE,
+        CUSTOM_PART,
+        &xml[insert_at..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn core_properties_replace_insert_and_expand() {
+        let xml = concat!(
+            r#"<cp:coreProperties xmlns:cp="cp" xmlns:dc="dc">"#,
+            r#"<dc:title>Old</dc:title><dc:subject/>"#,
+            r#"</cp:coreProperties>"#,
+        );
+        let result = apply_core_properties(
+            xml,
+            &props(&[("dc:title", "New & improved"), ("dc:subject", "Q3"), ("cp:lastModifiedBy", "Billing")]),
+        );
+        assert_eq!(
+            result,
+            concat!(
+                r#"<cp:coreProperties xmlns:cp="cp" xmlns:dc="dc">"#,
+                r#"<dc:title>New &amp; improved</dc:title><dc:subject>Q3</dc:subject>"#,
+                r#"<cp:lastModifiedBy>Billing</cp:lastModifiedBy>"#,
+                r#"</cp:coreProperties>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn core_property_prefix_is_not_confused_with_longer_names() {
+        let xml = r#"<cp:coreProperties><dc:titleExtra>x</dc:titleExtra></cp:coreProperties>"#;
+        let result = apply_core_properties(xml, &props(&[("dc:title", "T")]));
+        assert!(result.contains("<dc:titleExtra>x</dc:titleExtra><dc:title>T</dc:title>"), "{}", result);
+    }
+
+    #[test]
+    fn custom_properties_update_and_append() {
+        let xml = concat!(
+            r#"<Properties><property fmtid="f" pid="2" name="Reference"><vt:lpwstr>old</vt:lpwstr></property>"#,
+            r#"</Properties>"#,
+        );
+        let result = apply_custom_properties(xml, &props(&[("Reference", "R-1"), ("Batch", "7")]));
+        assert!(result.contains(r#"pid="2" name="Reference"><vt:lpwstr>R-1</vt:lpwstr>"#), "{}", result);
+        assert!(result.contains(r#"pid="3" name="Batch"><vt:lpwstr>7</vt:lpwstr>"#), "{}", result);
+        assert!(!result.contains("old"), "{}", result);
+    }
+
+    #[test]
+    fn new_custom_part_is_registered() {
+        let part = new_custom_properties_part(&props(&[("Batch", "7")]));
+        assert!(part.contains(r#"pid="2" name="Batch""#), "{}", part);
+
+        let types = register_custom_part_content_type("<Types></Types>");
+        assert!(types.contains(r#"PartName="/docProps/custom.xml""#), "{}", types);
+
+        let rels = register_custom_part_relationship(r#"<Relationships><Relationship Id="rIdCustomProperties"/></Relationships>"#);
+        assert!(rels.contains(r#"Id="rIdCustomProperties1""#), "{}", rels);
+    }
+}
@@ -0,0 +1,362 @@
+//! Filling a part from serialized data, repeating sections for sequences.
+//!
+//! Before sections are expanded, every placeholder in the part's text is
+//! replaced by a token holding it as written, so a placeholder split across
+//! runs sits in one text element and sections can be cut out of the XML
+//! around it. Tokens are delimited by two private use characters, which XML
+//! leaves unescaped, picked per part among those the part doesn't contain.
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+use docxide_template_core::is_wordprocessing;
+use quick_xml::events::Event;
+use quick_xml::NsReader;
+use serde_json::Value;
+
+use crate::escape_xml;
+
+/// The characters delimiting tokens in one part.
+#[derive(Clone, Copy)]
+pub(crate) struct TokenDelimiters {
+    open: char,
+    close: char,
+}
+
+impl TokenDelimiters {
+    /// The first two private use characters that don't occur in `xml`, or
+    /// `None` if it contains all of them.
+    pub(crate) fn for_part(xml: &str) -> Option<Self> {
+        let used: HashSet<char> = xml.chars().filter(|c| ('\u{E000}'..='\u{F8FF}').contains(c)).collect();
+        let mut free = ('\u{E000}'..='\u{F8FF}').filter(|c| !used.contains(c));
+        Some(Self { open: free.next()?, close: free.next()? })
+    }
+
+    /// The token standing in for `placeholder` while sections are expanded.
+    pub(crate) fn token(&self, placeholder: &str) -> String {
+        format!("{}{}{}", self.open, placeholder, self.close)
+    }
+}
+
+/// Looks up a dotted `name`, like `customer.address.city` or `items.0.name`,
+/// in the innermost scope that has its first segment. `.` is the innermost
+/// scope itself, the current item of a section.
+pub(crate) fn resolve<'v>(name: &str, scopes: &[&'v Value]) -> Option<&'v Value> {
+    if name == "." {
+        return scopes.last().copied();
+    }
+    let mut segments = name.split('.').map(str::trim);
+    let first = segments.next()?;
+    let mut value = scopes.iter().rev().find_map(|scope| scope.get(first))?;
+    for segment in segments {
+        value = match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => value.get(segment)?,
+        };
+    }
+    Some(value)
+}
+
+/// The text a value renders as, or `None` for objects and sequences.
+pub(crate) fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null => Some(String::new()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// What a section is rendered for: each item of a sequence, once for any other
+/// value, and not at all for `false`, `null`, empty strings or missing values.
+fn items(value: Option<&Value>) -> Vec<&Value> {
+    match value {
+        Some(Value::Array(items)) => items.iter().collect(),
+        None | Some(Value::Null | Value::Bool(false)) => Vec::new(),
+        Some(Value::String(s)) if s.is_empty() => Vec::new(),
+        Some(value) => vec![value],
+    }
+}
+
+/// Fills the tokens of `xml` from `root`, repeating `{#name}`...`{/name}`
+/// sections per item. Placeholders without a value are put back as written.
+pub(crate) fn expand_sections(xml: &str, delimiters: TokenDelimiters, root: &Value) -> String {
+    let part = Part::parse(xml, delimiters);
+    let mut result = String::with_capacity(xml.len());
+    part.render(0..xml.len(), &mut vec![root], &mut result);
+    result
+}
+
+struct Token {
+    /// Byte range of the token in the part.
+    start: usize,
+    end: usize,
+    /// The placeholder as written, braces included.
+    placeholder: String,
+}
+
+impl Token {
+    fn name(&self) -> &str {
+        let inner = self.placeholder.strip_prefix('{').and_then(|p| p.strip_suffix('}'));
+        inner.unwrap_or(&self.placeholder).trim()
+    }
+
+    fn opens(&self) -> Option<&str> {
+        self.name().strip_prefix('#').map(str::trim)
+    }
+
+    fn closes(&self) -> Option<&str> {
+        self.name().strip_prefix('/').map(str::trim)
+    }
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    Paragraph,
+    Row,
+    Cell,
+}
+
+struct Element {
+    kind: Kind,
+    start: usize,
+    end: usize,
+    /// Start of the parent element, whatever it is.
+    parent: Option<usize>,
+}
+
+struct Part<'a> {
+    xml: &'a str,
+    tokens: Vec<Token>,
+    /// Paragraphs, table rows and table cells.
+    elements: Vec<Element>,
+}
+
+impl<'a> Part<'a> {
+    fn parse(xml: &'a str, delimiters: TokenDelimiters) -> Self {
+        let mut tokens = Vec::new();
+        let mut rest = 0;
+        while let Some(open) = xml[rest..].find(delimiters.open).map(|pos| rest + pos) {
+            let inner = open + delimiters.open.len_utf8();
+            let Some(close) = xml[inner..].find(delimiters.close).map(|pos| inner + pos) else {
+                break;
+            };
+            let raw = &xml[inner..close];
+            let placeholder = quick_xml::escape::unescape(raw).map_or_else(|_| raw.to_string(), |p| p.into_owned());
+            let end = close + delimiters.close.len_utf8();
+            tokens.push(Token { start: open, end, placeholder });
+            rest = end;
+        }
+
+        let mut elements = Vec::new();
+        let mut reader = NsReader::from_str(xml);
+        reader.config_mut().check_end_names = false;
+        let mut open: Vec<(Option<Kind>, usize)> = Vec::new();
+        loop {
+            let before = reader.buffer_position() as usize;
+            match reader.read_resolved_event() {
+                Ok((ns, Event::Start(e))) => {
+                    let kind = match e.local_name().as_ref() {
+                        b"p" if is_wordprocessing(&ns) => Some(Kind::Paragraph),
+                        b"tr" if is_wordprocessing(&ns) => Some(Kind::Row),
+                        b"tc" if is_wordprocessing(&ns) => Some(Kind::Cell),
+                        _ => None,
+                    };
+                    open.push((kind, before));
+                }
+                Ok((_, Event::End(_))) => {
+                    if let Some((Some(kind), start)) = open.pop() {
+                        let parent = open.last().map(|(_, start)| *start);
+                        elements.push(Element { kind, start, end: reader.buffer_position() as usize, parent });
+                    }
+                }
+                Ok((_, Event::Eof)) | Err(_) => break,
+                _ => {}
+            }
+        }
+        Self { xml, tokens, elements }
+    }
+
+    /// The innermost element of `kind` around byte `pos`.
+    fn around(&self, kind: Kind, pos: usize) -> Option<&Element> {
+        self.elements
+            .iter()
+            .filter(|e| e.kind == kind && e.start <= pos && pos < e.end)
+            .max_by_key(|e| e.start)
+    }
+
+    /// The token closing the section opened by token `open`, if any before `limit`.
+    fn closing(&self, open: usize, name: &str, limit: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(open + 1) {
+            if token.end > limit {
+                break;
+            }
+            if token.opens() == Some(name) {
+                depth += 1;
+            } else if token.closes() == Some(name) {
+                if depth == 0 {
+                    return Some(i);
+                }
+                depth -= 1;
+            }
+        }
+        None
+    }
+
+    /// The range a section replaces and the pieces of it rendered per item.
+    ///
+    /// Markers in different cells of a table row repeat the row. Markers in
+    /// different paragraphs repeat the paragraphs between them, leaving out
+    /// the paragraphs holding the markers. Markers in the same paragraph
+    /// repeat the content between them.
+    fn layout(&self, open: &Token, close: &Token) -> Option<(Range<usize>, Vec<Range<usize>>)> {
+        if let (Some(row), Some(close_row)) = (self.around(Kind::Row, open.start), self.around(Kind::Row, close.start)) {
+            let cells = (self.around(Kind::Cell, open.start), self.around(Kind::Cell, close.start));
+            if row.start == close_row.start && cells.0.map(|c| c.start) != cells.1.map(|c| c.start) {
+                let pieces = vec![row.start..open.start, open.end..close.start, close.end..row.end];
+                return Some((row.start..row.end, pieces));
+            }
+        }
+        let paragraphs = (self.around(Kind::Paragraph, open.start), self.around(Kind::Paragraph, close.start));
+        match paragraphs {
+            (Some(first), Some(last)) if first.start == last.start => {
+                Some((open.start..close.end, std::iter::once(open.end..close.start).collect()))
+            }
+            (Some(first), Some(last)) if first.parent == last.parent && first.end <= last.start => {
+                Some((first.start..last.end, std::iter::once(first.end..last.start).collect()))
+            }
+            _ => None,
+        }
+    }
+
+    fn render(&self, range: Range<usize>, scopes: &mut Vec<&Value>, result: &mut String) {
+        let mut pos = range.start;
+        let mut i = self.tokens.partition_point(|t| t.start < range.start);
+        while let Some(token) = self.tokens.get(i).filter(|t| t.end <= range.end) {
+            let section = token.opens().and_then(|name| {
+                let close = self.closing(i, name, range.end)?;
+                Some((name, self.layout(token, &self.tokens[close])?))
+            });
+            if let Some((name, (outer, pieces))) = section {
+                result.push_str(&self.xml[pos..outer.start]);
+                for item in items(resolve(name, scopes)) {
+                    scopes.push(item);
+                    for piece in &pieces {
+                        self.render(piece.clone(), scopes, result);
+                    }
+                    scopes.pop();
+                }
+                pos = outer.end;
+                i = self.tokens.partition_point(|t| t.start < pos);
+                continue;
+            }
+
+            result.push_str(&self.xml[pos..token.start]);
+            let value = resolve(token.name(), scopes).and_then(text);
+            result.push_str(&escape_xml(value.as_deref().unwrap_or(&token.placeholder)));
+            pos = token.end;
+            i += 1;
+        }
+        result.push_str(&self.xml[pos..range.end]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DELIMITERS: TokenDelimiters = TokenDelimiters { open: '\u{E000}', close: '\u{E001}' };
+
+    fn token(placeholder: &str) -> String {
+        DELIMITERS.token(placeholder)
+    }
+
+    fn paragraph(text: &str) -> String {
+        format!("<w:p><w:r><w:t>{}</w:t></w:r></w:p>", text)
+    }
+
+    fn row(cells: &[&str]) -> String {
+        let cells: String = cells.iter().map(|text| format!("<w:tc>{}</w:tc>", paragraph(text))).collect();
+        format!("<w:tr>{}</w:tr>", cells)
+    }
+
+    #[test]
+    fn resolves_dotted_names_through_scopes() {
+        let root = json!({ "customer": { "name": "Acme", "tags": ["a", "b"] }, "total": 12.5 });
+        let item = json!({ "qty": 2 });
+        let scopes = [&root, &item];
+        assert_eq!(resolve("customer.name", &scopes), Some(&json!("Acme")));
+        assert_eq!(resolve("customer.tags.1", &scopes), Some(&json!("b")));
+        assert_eq!(resolve("qty", &scopes).and_then(text).as_deref(), Some("2"));
+        assert_eq!(resolve("total", &scopes).and_then(text).as_deref(), Some("12.5"));
+        assert_eq!(resolve(".", &scopes), Some(&item));
+        assert_eq!(resolve("missing", &scopes), None);
+    }
+
+    #[test]
+    fn repeats_paragraphs_between_markers() {
+        let xml = [
+            paragraph(&token("{title}")),
+            paragraph(&token("{#items}")),
+            paragraph(&format!("{} x{}", token("{ name }"), token("{qty}"))),
+            paragraph(&token("{/items}")),
+            paragraph("End"),
+        ]
+        .concat();
+        let data = json!({ "title": "Order", "items": [{ "name": "Pen", "qty": 2 }, { "name": "Ink", "qty": 1 }] });
+        let expected = [paragraph("Order"), paragraph("Pen x2"), paragraph("Ink x1"), paragraph("End")].concat();
+        assert_eq!(expand_sections(&xml, DELIMITERS, &data), expected);
+    }
+
+    #[test]
+    fn repeats_table_rows() {
+        let xml = format!(
+            "<w:tbl>{}{}</w:tbl>",
+            row(&["Name", "Qty"]),
+            row(&[&format!("{}{}", token("{#items}"), token("{name}")), &format!("{}{}", token("{qty}"), token("{/items}"))]),
+        );
+        let data = json!({ "items": [{ "name": "Pen", "qty": 2 }, { "name": "Ink", "qty": 1 }] });
+        let expected = format!("<w:tbl>{}{}{}</w:tbl>", row(&["Name", "Qty"]), row(&["Pen", "2"]), row(&["Ink", "1"]));
+        assert_eq!(expand_sections(&xml, DELIMITERS, &data), expected);
+    }
+
+    #[test]
+    fn repeats_inline_content_and_nests() {
+        let xml = paragraph(&format!(
+            "{}{}: {}{}, {}{}; {}",
+            token("{#groups}"),
+            token("{name}"),
+            token("{#tags}"),
+            token("{.}"),
+            token("{/tags}"),
+            token("{/groups}"),
+            token("{unknown}"),
+        ));
+        let data = json!({ "groups": [{ "name": "A", "tags": ["x", "y"] }, { "name": "B", "tags": [] }] });
+        assert_eq!(expand_sections(&xml, DELIMITERS, &data), paragraph("A: x, y, B: ; {unknown}"));
+    }
+
+    #[test]
+    fn sections_render_once_for_values_and_never_for_falsy_ones() {
+        let xml = [
+            paragraph(&format!("{}shown {}{}", token("{#address}"), token("{city}"), token("{/address}"))),
+            paragraph(&format!("{}hidden{}", token("{#vip}"), token("{/vip}"))),
+        ]
+        .concat();
+        let data = json!({ "address": { "city": "Oslo" }, "vip": false });
+        assert_eq!(expand_sections(&xml, DELIMITERS, &data), [paragraph("shown Oslo"), paragraph("")].concat());
+    }
+
+    #[test]
+    fn tokens_avoid_private_use_characters_in_the_part() {
+        let xml = paragraph("\u{E000}\u{E002} {name}");
+        let delimiters = TokenDelimiters::for_part(&xml).unwrap();
+        assert_eq!((delimiters.open, delimiters.close), ('\u{E001}', '\u{E003}'));
+
+        let xml = paragraph(&format!("\u{E000}\u{E002} {}", delimiters.token("{name}")));
+        assert_eq!(expand_sections(&xml, delimiters, &json!({ "name": "Ann" })), paragraph("\u{E000}\u{E002} Ann"));
+    }
+}
@@ -0,0 +1,177 @@
+"""
+Generate a .docx with an embedded bar chart and its workbook.
+
+The chart has three series over the categories in Sheet1!$A$2:$A$4:
+- "Revenue {revenue}" in column B
+- "{visitors}" in column C
+- "Target" in column D, not bound to any placeholder
+
+The body text also holds a {month} placeholder.
+
+Tests that chart series become fields and that filling them rewrites the
+chart caches and the embedded workbook.
+"""
+
+import io
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/chart_report.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+R_NS = "http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+WP_NS = "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
+A_NS = "http://schemas.openxmlformats.org/drawingml/2006/main"
+C_NS = "http://schemas.openxmlformats.org/drawingml/2006/chart"
+SS_NS = "http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+PKG_NS = "http://schemas.openxmlformats.org/package/2006/relationships"
+CT_NS = "http://schemas.openxmlformats.org/package/2006/content-types"
+
+CATEGORIES = ["Jan", "Feb", "Mar"]
+SERIES = [
+    ("B", "Revenue {revenue}", [100, 120, 140]),
+    ("C", "{visitors}", [10, 12, 14]),
+    ("D", "Target", [110, 110, 110]),
+]
+
+
+def zipped(entries):
+    buf = io.BytesIO()
+    with zipfile.ZipFile(buf, "w", zipfile.ZIP_DEFLATED) as z:
+        for name, text in entries:
+            z.writestr(zipfile.ZipInfo(name, date_time=(1980, 1, 1, 0, 0, 0)), text.encode("utf-8"),
+                       compress_type=zipfile.ZIP_DEFLATED)
+    return buf.getvalue()
+
+
+def cell(ref, value):
+    if isinstance(value, str):
+        return f'<c r="{ref}" t="inlineStr"><is><t>{value}</t></is></c>'
+    return f'<c r="{ref}"><v>{value}</v></c>'
+
+
+rows = [[("A1", "")] + [(f"{col}1", name) for col, name, _ in SERIES]]
+for i, category in enumerate(CATEGORIES):
+    r = i + 2
+    rows.append([(f"A{r}", category)] + [(f"{col}{r}", values[i]) for col, _, values in SERIES])
+sheet_data = "".join(
+    f'<row r="{i + 1}" spans="1:4">' + "".join(cell(ref, v) for ref, v in row) + "</row>"
+    for i, row in enumerate(rows)
+)
+
+workbook = zipped([
+    ("[Content_Types].xml",
+     f'<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n<Types xmlns="{CT_NS}">'
+     '<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>'
+     '<Default Extension="xml" ContentType="application/xml"/>'
+     '<Override PartName="/xl/workbook.xml" '
+     'ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>'
+     '<Override PartName="/xl/worksheets/sheet1.xml" '
+     'ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>'
+     "</Types>"),
+    ("_rels/.rels",
+     f'<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n<Relationships xmlns="{PKG_NS}">'
+     f'<Relationship Id="rId1" Type="{R_NS}/officeDocument" Target="xl/workbook.xml"/></Relationships>'),
+    ("xl/workbook.xml",
+     f'<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n<workbook xmlns="{SS_NS}" xmlns:r="{R_NS}">'
+     '<sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>'),
+    ("xl/_rels/workbook.xml.rels",
+     f'<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n<Relationships xmlns="{PKG_NS}">'
+     f'<Relationship Id="rId1" Type="{R_NS}/worksheet" Target="worksheets/sheet1.xml"/></Relationships>'),
+    ("xl/worksheets/sheet1.xml",
+     f'<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n<worksheet xmlns="{SS_NS}">'
+     f'<dimension ref="A1:D4"/><sheetData>{sheet_data}</sheetData></worksheet>'),
+])
+
+
+def series(idx, col, name, values):
+    cat_pts = "".join(f'<c:pt idx="{i}"><c:v>{c}</c:v></c:pt>' for i, c in enumerate(CATEGORIES))
+    val_pts = "".join(f'<c:pt idx="{i}"><c:v>{v}</c:v></c:pt>' for i, v in enumerate(values))
+    return (
+        f'<c:ser><c:idx val="{idx}"/><c:order val="{idx}"/>'
+        f'<c:tx><c:strRef><c:f>Sheet1!${col}$1</c:f><c:strCache><c:ptCount val="1"/>'
+        f'<c:pt idx="0"><c:v>{name}</c:v></c:pt></c:strCache></c:strRef></c:tx>'
+        '<c:invertIfNegative val="0"/>'
+        f'<c:cat><c:strRef><c:f>Sheet1!$A$2:$A$4</c:f><c:strCache><c:ptCount val="3"/>{cat_pts}'
+        "</c:strCache></c:strRef></c:cat>"
+        f'<c:val><c:numRef><c:f>Sheet1!${col}$2:${col}$4</c:f><c:numCache><c:formatCode>General</c:formatCode>'
+        f'<c:ptCount val="3"/>{val_pts}</c:numCache></c:numRef></c:val>'
+        "</c:ser>"
+    )
+
+
+chart = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<c:chartSpace xmlns:c="{C_NS}" xmlns:a="{A_NS}" xmlns:r="{R_NS}">'
+    '<c:chart><c:autoTitleDeleted val="1"/><c:plotArea><c:layout/>'
+    '<c:barChart><c:barDir val="col"/><c:grouping val="clustered"/><c:varyColors val="0"/>'
+    + "".join(series(i, col, name, values) for i, (col, name, values) in enumerate(SERIES))
+    + '<c:gapWidth val="150"/><c:axId val="1"/><c:axId val="2"/></c:barChart>'
+    '<c:catAx><c:axId val="1"/><c:scaling><c:orientation val="minMax"/></c:scaling><c:delete val="0"/>'
+    '<c:axPos val="b"/><c:crossAx val="2"/></c:catAx>'
+    '<c:valAx><c:axId val="2"/><c:scaling><c:orientation val="minMax"/></c:scaling><c:delete val="0"/>'
+    '<c:axPos val="l"/><c:crossAx val="1"/></c:valAx>'
+    "</c:plotArea><c:legend><c:legendPos val=\"r\"/></c:legend><c:plotVisOnly val=\"1\"/></c:chart>"
+    '<c:externalData r:id="rId1"><c:autoUpdate val="0"/></c:externalData>'
+    "</c:chartSpace>"
+)
+
+chart_rels = (
+    f'<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n<Relationships xmlns="{PKG_NS}">'
+    f'<Relationship Id="rId1" Type="{R_NS}/package" Target="../embeddings/Microsoft_Excel_Worksheet.xlsx"/>'
+    "</Relationships>"
+)
+
+drawing = (
+    "<w:p><w:r><w:drawing>"
+    '<wp:inline distT="0" distB="0" distL="0" distR="0">'
+    '<wp:extent cx="5486400" cy="3200400"/>'
+    '<wp:docPr id="1" name="Chart 1"/>'
+    f'<a:graphic xmlns:a="{A_NS}"><a:graphicData uri="{C_NS}">'
+    f'<c:chart xmlns:c="{C_NS}" r:id="rIdChart1"/>'
+    "</a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"
+)
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}" xmlns:r="{R_NS}" xmlns:wp="{WP_NS}"><w:body>'
+    '<w:p><w:r><w:t xml:space="preserve">KPI report for {month}</w:t></w:r></w:p>'
+    + drawing
+    + '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr>'
+    + "</w:body></w:document>"
+)
+
+content_types = (
+    '<Override PartName="/word/charts/chart1.xml" '
+    'ContentType="application/vnd.openxmlformats-officedocument.drawingml.chart+xml"/>'
+    '<Default Extension="xlsx" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"/>'
+)
+relationships = f'<Relationship Id="rIdChart1" Type="{R_NS}/chart" Target="charts/chart1.xml"/>'
+
+
+def patch(name, data):
+    if name == "word/document.xml":
+        return document.encode("utf-8")
+    text = data.decode("utf-8")
+    if name == "[Content_Types].xml":
+        text = text.replace("</Types>", content_types + "</Types>")
+    if name == "word/_rels/document.xml.rels":
+        text = text.replace("</Relationships>", relationships + "</Relationships>")
+    return text.encode("utf-8")
+
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    # The workbook goes first, ahead of the chart that refers to it
+    dst.writestr(zipfile.ZipInfo("word/embeddings/Microsoft_Excel_Worksheet.xlsx", date_time=(1980, 1, 1, 0, 0, 0)),
+                 workbook, compress_type=zipfile.ZIP_DEFLATED)
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename.endswith(".xml") or item.filename.endswith(".rels"):
+            data = patch(item.filename, data)
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+    for name, text in [("word/charts/chart1.xml", chart), ("word/charts/_rels/chart1.xml.rels", chart_rels)]:
+        dst.writestr(zipfile.ZipInfo(name, date_time=(1980, 1, 1, 0, 0, 0)), text.encode("utf-8"),
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
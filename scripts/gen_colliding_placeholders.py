@@ -0,0 +1,40 @@
+"""
+Generate a .docx with placeholder names that make the same field name.
+
+{FirstName}, {first_name} and {first-name} all become `first_name`, while
+{ FirstName } is the same name as {FirstName} with whitespace around it.
+
+Tests the `collisions` policies: merging the spellings into one field, or
+giving each spelling after the first a suffixed field of its own.
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/colliding_placeholders.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+
+paragraphs = [
+    "Dear {FirstName} {lastName},",
+    "Thank you, { FirstName }.",
+    "Signed for {first_name}",
+    "Copy to {first-name}",
+]
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}"><w:body>'
+    + "".join(f"<w:p><w:r><w:t>{text}</w:t></w:r></w:p>" for text in paragraphs)
+    + '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename == "word/document.xml":
+            data = document.encode("utf-8")
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
@@ -0,0 +1,73 @@
+"""
+Generate a .docx with placeholders inside dropdown, combo box and date picker
+content controls (w:sdt).
+
+python-docx has no API for content controls, so the document part is written
+by hand on top of HelloWorld.docx.
+
+Tests that the macro reads the controls' list items and date format and
+generates typed fields for the placeholders inside them.
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/content_controls.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+
+
+def run(text):
+    return f'<w:r><w:t xml:space="preserve">{text}</w:t></w:r>'
+
+
+def sdt(properties, content):
+    return (
+        f"<w:sdt><w:sdtPr>{properties}</w:sdtPr>"
+        f"<w:sdtContent>{content}</w:sdtContent></w:sdt>"
+    )
+
+
+def list_items(*items):
+    return "".join(
+        f'<w:listItem w:displayText="{text}" w:value="{text.lower()}"/>' for text in items
+    )
+
+
+body = "".join([
+    f"<w:p>{run('Customer: {customer}')}</w:p>",
+    "<w:p>" + run("Status: ") + sdt(
+        '<w:alias w:val="Status"/><w:dropDownList>'
+        + list_items("Draft", "Sent", "Paid")
+        + "</w:dropDownList>",
+        run("{") + run("status}"),
+    ) + "</w:p>",
+    "<w:p>" + run("Priority: ") + sdt(
+        '<w:alias w:val="Priority"/><w:comboBox>'
+        + list_items("Low", "Normal", "High")
+        + "</w:comboBox>",
+        run("{priority}"),
+    ) + "</w:p>",
+    "<w:p>" + run("Due: ") + sdt(
+        '<w:alias w:val="Due date"/><w:date><w:dateFormat w:val="dd.MM.yyyy"/>'
+        '<w:lid w:val="nb-NO"/><w:storeMappedDataAs w:val="dateTime"/>'
+        '<w:calendar w:val="gregorian"/></w:date>',
+        run("{due_date}"),
+    ) + "</w:p>",
+])
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}"><w:body>{body}'
+    '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename == "word/document.xml":
+            data = document.encode("utf-8")
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
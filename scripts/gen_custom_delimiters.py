@@ -0,0 +1,46 @@
+"""
+Generate a .docx that uses {{ }} as placeholder delimiters and also contains
+literal braces in a JSON snippet.
+
+Tests that generate_templates!(..., delimiters = ("{{", "}}")) only picks up
+the double-brace placeholders and leaves the JSON alone.
+"""
+
+import os
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT_DIR = "test-crate/delimiter_templates"
+OUT = f"{OUT_DIR}/code_sample.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+
+
+def paragraph(*runs):
+    return "<w:p>" + "".join(
+        f'<w:r><w:t xml:space="preserve">{text}</w:t></w:r>' for text in runs
+    ) + "</w:p>"
+
+
+body = "".join([
+    paragraph("Dear {{ name }},"),
+    paragraph('Set your config to {"debug": true, "level": 3} and restart.'),
+    paragraph("Regards, {{", "sender}}"),
+])
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}"><w:body>{body}'
+    '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+os.makedirs(OUT_DIR, exist_ok=True)
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename == "word/document.xml":
+            data = document.encode("utf-8")
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
@@ -0,0 +1,67 @@
+"""
+Generate a .docx with placeholders in the document properties:
+core title and subject, and a custom property.
+
+python-docx can't write custom properties, so the parts are written by hand
+on top of HelloWorld.docx.
+
+Tests that placeholders in docProps/*.xml become fields and are replaced.
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/document_properties.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}"><w:body>'
+    "<w:p><w:r><w:t>Prepared for {client}.</w:t></w:r></w:p>"
+    '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+custom = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    '<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" '
+    'xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes">'
+    '<property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Reference">'
+    "<vt:lpwstr>{reference}</vt:lpwstr></property></Properties>"
+)
+
+
+def patch(name, data):
+    text = data.decode("utf-8")
+    if name == "word/document.xml":
+        return document.encode("utf-8")
+    if name == "docProps/core.xml":
+        text = text.replace("<dc:title></dc:title>", "<dc:title>{doc_title}</dc:title>")
+        text = text.replace("<dc:subject></dc:subject>", "<dc:subject>Report for {client}</dc:subject>")
+    if name == "[Content_Types].xml":
+        text = text.replace(
+            "</Types>",
+            '<Override PartName="/docProps/custom.xml" '
+            'ContentType="application/vnd.openxmlformats-officedocument.custom-properties+xml"/></Types>',
+        )
+    if name == "_rels/.rels":
+        text = text.replace(
+            "</Relationships>",
+            '<Relationship Id="rId4" '
+            'Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties" '
+            'Target="docProps/custom.xml"/></Relationships>',
+        )
+    return text.encode("utf-8")
+
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename.endswith(".xml") or item.filename.endswith(".rels"):
+            data = patch(item.filename, data)
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+    dst.writestr(zipfile.ZipInfo("docProps/custom.xml", date_time=(1980, 1, 1, 0, 0, 0)),
+                 custom.encode("utf-8"), compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
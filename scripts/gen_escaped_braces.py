@@ -0,0 +1,45 @@
+"""
+Generate a .docx with escaped braces next to real placeholders.
+
+  \\{name} renders as a literal "{name}" and is not a field.
+  \\{"retries": 3\\} renders as the JSON object {"retries": 3}.
+  The escape in the last paragraph is split across runs.
+
+Tests that escapes are skipped by the macro and unescaped in the output.
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/escaped_braces.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+
+
+def paragraph(*runs):
+    return "<w:p>" + "".join(
+        f'<w:r><w:t xml:space="preserve">{text}</w:t></w:r>' for text in runs
+    ) + "</w:p>"
+
+
+body = "".join([
+    paragraph("Write \\{name} to greet {name}."),
+    paragraph('Config: \\{"retries": 3\\}'),
+    paragraph("Literal \\", "{value\\}", " next to {value}"),
+])
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}"><w:body>{body}'
+    '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename == "word/document.xml":
+            data = document.encode("utf-8")
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
@@ -0,0 +1,35 @@
+"""
+Generate a .docx whose placeholder names are Rust keywords.
+
+Tests that the pipeline handles:
+- Keywords, which become raw identifiers ({type} -> r#type)
+- Mixed-case keywords ({Match} -> r#match)
+- Edition 2018 keywords ({async})
+- Keywords that can't be raw identifiers ({Self} -> self_)
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/keyword_placeholders.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+
+paragraphs = ["Type: {type}", "Match: {Match}", "Async: {async}", "Self: {Self}"]
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}"><w:body>'
+    + "".join(f"<w:p><w:r><w:t>{text}</w:t></w:r></w:p>" for text in paragraphs)
+    + '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename == "word/document.xml":
+            data = document.encode("utf-8")
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
@@ -0,0 +1,102 @@
+"""
+Generate a .docx with placeholders outside of plain run text.
+
+- An inline picture whose alt text (wp:docPr descr) and title hold placeholders
+- A hyperlink whose address holds a literal {user_id} placeholder
+- A hyperlink whose address holds %7Bslug%7D, as Word encodes typed braces
+- A HYPERLINK field whose instruction (w:instrText) is split across runs
+
+Tests that alt text, hyperlink targets and field codes are scanned and filled.
+"""
+
+import base64
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/links_and_fields.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+R_NS = "http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+WP_NS = "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"
+A_NS = "http://schemas.openxmlformats.org/drawingml/2006/main"
+PIC_NS = "http://schemas.openxmlformats.org/drawingml/2006/picture"
+
+# 1x1 transparent PNG
+PNG = base64.b64decode(
+    "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg=="
+)
+
+picture = (
+    "<w:p><w:r><w:drawing>"
+    '<wp:inline distT="0" distB="0" distL="0" distR="0">'
+    '<wp:extent cx="914400" cy="914400"/>'
+    '<wp:docPr id="1" name="Picture 1" descr="Photo of {person}" title="{photo_title}"/>'
+    f'<a:graphic xmlns:a="{A_NS}"><a:graphicData uri="{PIC_NS}">'
+    f'<pic:pic xmlns:pic="{PIC_NS}">'
+    '<pic:nvPicPr><pic:cNvPr id="0" name="photo.png"/><pic:cNvPicPr/></pic:nvPicPr>'
+    '<pic:blipFill><a:blip r:embed="rIdPhoto"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>'
+    '<pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="914400" cy="914400"/></a:xfrm>'
+    '<a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr>'
+    "</pic:pic></a:graphicData></a:graphic>"
+    "</wp:inline></w:drawing></w:r></w:p>"
+)
+
+
+def hyperlink(rel_id, text):
+    return (
+        f'<w:p><w:hyperlink r:id="{rel_id}"><w:r><w:rPr><w:rStyle w:val="Hyperlink"/></w:rPr>'
+        f"<w:t>{text}</w:t></w:r></w:hyperlink></w:p>"
+    )
+
+
+field = (
+    '<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r>'
+    '<w:r><w:instrText xml:space="preserve"> HYPERLINK "{docs</w:instrText></w:r>'
+    '<w:r><w:instrText xml:space="preserve">_url}" </w:instrText></w:r>'
+    '<w:r><w:fldChar w:fldCharType="separate"/></w:r>'
+    "<w:r><w:t>Documentation</w:t></w:r>"
+    '<w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>'
+)
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}" xmlns:r="{R_NS}" xmlns:wp="{WP_NS}"><w:body>'
+    + picture
+    + hyperlink("rIdProfile", "Profile")
+    + hyperlink("rIdArticle", "Article")
+    + field
+    + '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr>'
+    + "</w:body></w:document>"
+)
+
+relationships = (
+    f'<Relationship Id="rIdPhoto" Type="{R_NS}/image" Target="media/photo.png"/>'
+    f'<Relationship Id="rIdProfile" Type="{R_NS}/hyperlink" '
+    'Target="https://example.com/users/{user_id}" TargetMode="External"/>'
+    f'<Relationship Id="rIdArticle" Type="{R_NS}/hyperlink" '
+    'Target="https://example.com/articles/%7Bslug%7D" TargetMode="External"/>'
+)
+
+
+def patch(name, data):
+    if name == "word/document.xml":
+        return document.encode("utf-8")
+    text = data.decode("utf-8")
+    if name == "[Content_Types].xml" and 'Extension="png"' not in text:
+        text = text.replace("</Types>", '<Default Extension="png" ContentType="image/png"/></Types>')
+    if name == "word/_rels/document.xml.rels":
+        text = text.replace("</Relationships>", relationships + "</Relationships>")
+    return text.encode("utf-8")
+
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename.endswith(".xml") or item.filename.endswith(".rels"):
+            data = patch(item.filename, data)
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+    dst.writestr(zipfile.ZipInfo("word/media/photo.png", date_time=(1980, 1, 1, 0, 0, 0)), PNG,
+                 compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
@@ -0,0 +1,105 @@
+"""
+Generate a .docx with two sections, each with its own headers and footers.
+
+The first section's properties live in a paragraph-level w:sectPr, which the
+old docx-rs based extraction never looked at. It has a default and a
+first-page header. The final (body-level) section has a default header and
+default and even-page footers.
+
+Tests that headers and footers of every section and type are scanned.
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/multi_section.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+R_NS = "http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+
+# part name -> (kind, text)
+parts = {
+    "header1.xml": ("header", "{cover_header}"),
+    "header2.xml": ("header", "{title_page_header}"),
+    "header3.xml": ("header", "Chapter: {chapter_header}"),
+    "footer1.xml": ("footer", "{chapter_footer}"),
+    "footer2.xml": ("footer", "Even pages: {even_footer}"),
+}
+rel_ids = {name: f"rIdHf{i}" for i, name in enumerate(parts)}
+
+
+def paragraph(text, section=""):
+    ppr = f"<w:pPr>{section}</w:pPr>" if section else ""
+    return f'<w:p>{ppr}<w:r><w:t xml:space="preserve">{text}</w:t></w:r></w:p>'
+
+
+def reference(kind, ref_type, name):
+    return f'<w:{kind}Reference w:type="{ref_type}" r:id="{rel_ids[name]}"/>'
+
+
+first_section = (
+    "<w:sectPr>"
+    + reference("header", "default", "header1.xml")
+    + reference("header", "first", "header2.xml")
+    + '<w:pgSz w:w="12240" w:h="15840"/><w:titlePg/></w:sectPr>'
+)
+last_section = (
+    "<w:sectPr>"
+    + reference("header", "default", "header3.xml")
+    + reference("footer", "default", "footer1.xml")
+    + reference("footer", "even", "footer2.xml")
+    + '<w:pgSz w:w="12240" w:h="15840"/></w:sectPr>'
+)
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:document xmlns:w="{W_NS}" xmlns:r="{R_NS}"><w:body>'
+    + paragraph("Cover page", first_section)
+    + paragraph("Chapter text: {body_text}")
+    + last_section
+    + "</w:body></w:document>"
+)
+
+
+def header_footer(kind, text):
+    tag = "hdr" if kind == "header" else "ftr"
+    return (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+        f'<w:{tag} xmlns:w="{W_NS}" xmlns:r="{R_NS}">{paragraph(text)}</w:{tag}>'
+    )
+
+
+content_types = "".join(
+    f'<Override PartName="/word/{name}" '
+    f'ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.{kind}+xml"/>'
+    for name, (kind, _) in parts.items()
+)
+relationships = "".join(
+    f'<Relationship Id="{rel_ids[name]}" Type="{R_NS}/{kind}" Target="{name}"/>'
+    for name, (kind, _) in parts.items()
+)
+
+
+def patch(name, data):
+    if name == "word/document.xml":
+        return document.encode("utf-8")
+    text = data.decode("utf-8")
+    if name == "[Content_Types].xml":
+        text = text.replace("</Types>", content_types + "</Types>")
+    if name == "word/_rels/document.xml.rels":
+        text = text.replace("</Relationships>", relationships + "</Relationships>")
+    return text.encode("utf-8")
+
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename.endswith(".xml") or item.filename.endswith(".rels"):
+            data = patch(item.filename, data)
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+    for name, (kind, text) in parts.items():
+        dst.writestr(zipfile.ZipInfo(f"word/{name}", date_time=(1980, 1, 1, 0, 0, 0)),
+                     header_footer(kind, text).encode("utf-8"), compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
@@ -0,0 +1,133 @@
+"""
+Generate a .docx with placeholders outside the main body text:
+a footnote, an endnote, a comment and a text box. The text box is stored the
+way Word writes it, inside mc:AlternateContent with a DrawingML choice and a
+VML fallback.
+
+python-docx can't create any of these, so the parts are written by hand on
+top of HelloWorld.docx.
+
+Tests that the macro discovers placeholders in all of these locations.
+"""
+
+import zipfile
+
+BASE = "test-crate/templates/HelloWorld.docx"
+OUT = "test-crate/templates/notes_and_text_boxes.docx"
+
+W_NS = "http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+NAMESPACES = (
+    f'xmlns:w="{W_NS}" '
+    'xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" '
+    'xmlns:mc="http://schemas.openxmlformats.org/markup-compatibility/2006" '
+    'xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" '
+    'xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" '
+    'xmlns:wps="http://schemas.microsoft.com/office/word/2010/wordprocessingShape" '
+    'xmlns:v="urn:schemas-microsoft-com:vml" '
+    'mc:Ignorable="wps"'
+)
+
+
+def run(text):
+    return f'<w:r><w:t xml:space="preserve">{text}</w:t></w:r>'
+
+
+def paragraph(*content):
+    return "<w:p>" + "".join(content) + "</w:p>"
+
+
+text_box_content = f"<w:txbxContent>{paragraph(run('{box_'), run('label}'))}</w:txbxContent>"
+
+text_box = (
+    "<w:r><mc:AlternateContent>"
+    "<mc:Choice Requires=\"wps\"><w:drawing><wp:anchor><wp:extent cx=\"1828800\" cy=\"457200\"/>"
+    "<wp:docPr id=\"1\" name=\"Text Box 1\"/>"
+    "<a:graphic><a:graphicData uri=\"http://schemas.microsoft.com/office/word/2010/wordprocessingShape\">"
+    f"<wps:wsp><wps:txbx>{text_box_content}</wps:txbx><wps:bodyPr/></wps:wsp>"
+    "</a:graphicData></a:graphic></wp:anchor></w:drawing></mc:Choice>"
+    "<mc:Fallback><w:pict><v:shape style=\"width:144pt;height:36pt\">"
+    f"<v:textbox>{text_box_content}</v:textbox></v:shape></w:pict></mc:Fallback>"
+    "</mc:AlternateContent></w:r>"
+)
+
+body = "".join([
+    paragraph(
+        run("See the note"),
+        '<w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:footnoteReference w:id="1"/></w:r>',
+        run(" and the endnote"),
+        '<w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:endnoteReference w:id="1"/></w:r>',
+        '<w:commentRangeStart w:id="0"/>',
+        run(" about {subject}."),
+        '<w:commentRangeEnd w:id="0"/><w:r><w:commentReference w:id="0"/></w:r>',
+    ),
+    paragraph(run("Box: "), text_box),
+])
+
+document = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f"<w:document {NAMESPACES}><w:body>{body}"
+    '<w:sectPr><w:pgSz w:w="12240" w:h="15840"/></w:sectPr></w:body></w:document>'
+)
+
+
+def notes(kind, text):
+    separators = (
+        f'<w:{kind} w:type="separator" w:id="-1"><w:p><w:r><w:separator/></w:r></w:p></w:{kind}>'
+        f'<w:{kind} w:type="continuationSeparator" w:id="0"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:{kind}>'
+    )
+    return (
+        '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+        f'<w:{kind}s xmlns:w="{W_NS}">{separators}'
+        f'<w:{kind} w:id="1">{paragraph(run(text))}</w:{kind}></w:{kind}s>'
+    )
+
+
+comments = (
+    '<?xml version="1.0" encoding="UTF-8" standalone="yes"?>\n'
+    f'<w:comments xmlns:w="{W_NS}">'
+    f'<w:comment w:id="0" w:author="Docxide" w:initials="D">{paragraph(run("Checked by {reviewer}"))}</w:comment>'
+    "</w:comments>"
+)
+
+parts = {
+    "word/footnotes.xml": (notes("footnote", "Source: {footnote_source}"), "footnotes"),
+    "word/endnotes.xml": (notes("endnote", "Printed {endnote_date}"), "endnotes"),
+    "word/comments.xml": (comments, "comments"),
+}
+
+content_types = "".join(
+    f'<Override PartName="/{name}" '
+    f'ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.{kind}+xml"/>'
+    for name, (_, kind) in parts.items()
+)
+relationships = "".join(
+    f'<Relationship Id="rIdNote{i}" '
+    f'Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/{kind}" '
+    f'Target="{name[len("word/"):]}"/>'
+    for i, (name, (_, kind)) in enumerate(parts.items())
+)
+
+
+def patch(name, data):
+    if name == "word/document.xml":
+        return document.encode("utf-8")
+    text = data.decode("utf-8")
+    if name == "[Content_Types].xml":
+        text = text.replace("</Types>", content_types + "</Types>")
+    if name == "word/_rels/document.xml.rels":
+        text = text.replace("</Relationships>", relationships + "</Relationships>")
+    return text.encode("utf-8")
+
+
+with zipfile.ZipFile(BASE) as src, zipfile.ZipFile(OUT, "w", zipfile.ZIP_DEFLATED) as dst:
+    for item in src.infolist():
+        data = src.read(item.filename)
+        if item.filename.endswith(".xml") or item.filename.endswith(".rels"):
+            data = patch(item.filename, data)
+        dst.writestr(zipfile.ZipInfo(item.filename, date_time=(1980, 1, 1, 0, 0, 0)), data,
+                     compress_type=zipfile.ZIP_DEFLATED)
+    for name, (xml, _) in parts.items():
+        dst.writestr(zipfile.ZipInfo(name, date_time=(1980, 1, 1, 0, 0, 0)), xml.encode("utf-8"),
+                     compress_type=zipfile.ZIP_DEFLATED)
+
+print(f"Saved to {OUT}")
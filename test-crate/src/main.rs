@@ -2,6 +2,115 @@ use docxide_template::generate_templates;
 
 generate_templates!("test-crate/templates");
 
+mod custom_delimiters {
+    use docxide_template::generate_templates;
+
+    generate_templates!("test-crate/delimiter_templates", delimiters = ("{{", "}}"));
+}
+
+mod configured {
+    #![deny(missing_docs, unreachable_pub)]
+    use docxide_template::generate_templates;
+
+    generate_templates!(
+        "test-crate/delimiter_templates",
+        delimiters = ("{{", "}}"),
+        derive = (PartialEq, Eq, Hash, Default),
+        attributes = (#[allow(missing_docs)], #[must_use]),
+        visibility = pub(crate),
+    );
+}
+
+mod derived {
+    use docxide_template::generate_templates;
+
+    generate_templates!(
+        "test-crate/templates",
+        derive = (PartialEq, PartialOrd),
+        attributes = (#[allow(dead_code)]),
+    );
+}
+
+mod alphabetical {
+    use docxide_template::generate_templates;
+
+    generate_templates!("test-crate/templates", order = alphabetical);
+}
+
+mod renamed {
+    use docxide_template::generate_templates;
+
+    generate_templates!(
+        "test-crate/templates",
+        rename = (
+            "Fornavn" => first_name,
+            "First Name" => first_name,
+            "Øltype" => beer_type,
+            "Størrelse" => size,
+            HelloWorld = ("firstName" => recipient),
+        ),
+    );
+}
+
+mod suffixed {
+    use docxide_template::generate_templates;
+
+    generate_templates!("test-crate/templates", collisions = suffix);
+}
+
+mod keyword_suffixed {
+    use docxide_template::generate_templates;
+
+    generate_templates!("test-crate/templates", keyword_suffix = "_value");
+}
+
+mod hand_written {
+    // Only constructed in tests
+    #![allow(dead_code)]
+    #![deny(missing_docs)]
+    use docxide_template::{Date, DocxTemplate};
+
+    /// A greeting, written by hand instead of generated.
+    #[derive(DocxTemplate)]
+    #[docx(path = "test-crate/templates/HelloWorld.docx")]
+    pub struct Greeting<'a> {
+        /// Who is greeted.
+        #[docx(rename = "firstName")]
+        pub name: &'a str,
+        /// What they're greeted with.
+        pub product_name: String,
+    }
+
+    /// The content controls template, with a generated dropdown type and a
+    /// number for the combo box.
+    #[derive(DocxTemplate)]
+    #[docx(path = "test-crate/templates/content_controls.docx")]
+    pub struct Order {
+        /// The customer's name.
+        pub customer: String,
+        /// The dropdown's status.
+        pub status: super::ContentControlsStatus,
+        /// A priority from 1 to 5.
+        pub priority: u8,
+        /// When the order is due.
+        pub due_date: Date,
+    }
+
+    /// [`Order`] with the dropdown's type as a parameter.
+    #[derive(DocxTemplate)]
+    #[docx(path = "test-crate/templates/content_controls.docx")]
+    pub struct GenericOrder<S: docxide_template::DropdownValue> {
+        /// The customer's name.
+        pub customer: String,
+        /// The dropdown's status.
+        pub status: S,
+        /// A priority from 1 to 5.
+        pub priority: u8,
+        /// When the order is due.
+        pub due_date: Date,
+    }
+}
+
 fn main() {
     let hw = HelloWorld::new("World", "docxide");
     hw.save("test-crate/output/hello_world").unwrap();
@@ -139,5 +248,565 @@ mod tests {
         assert!(!all.contains("cell_label"), "placeholder still present");
         assert!(!all.contains("cell_value"), "placeholder still present");
     }
-}
 
+    // -- Content controls --
+
+    fn content_controls() -> ContentControls {
+        ContentControls::new(
+            "Acme",
+            ContentControlsStatus::Paid,
+            ContentControlsPriority::Other("Urgent".into()),
+            docxide_template::Date::new(2024, 3, 5).unwrap(),
+        )
+    }
+
+    #[test]
+    fn content_controls_have_typed_fields() {
+        let c = content_controls();
+        assert_eq!(c.customer, "Acme");
+        assert_eq!(c.status, ContentControlsStatus::Paid);
+        assert_eq!(ContentControlsStatus::Draft.as_str(), "Draft");
+        assert_eq!(ContentControlsPriority::High.as_str(), "High");
+        assert_eq!(c.priority.as_str(), "Urgent");
+        assert_eq!(c.due_date.to_string(), "2024-03-05");
+    }
+
+    #[test]
+    fn content_controls_to_bytes_renders_values() {
+        let bytes = content_controls().to_bytes().unwrap();
+        let xml = read_zip_entry(&bytes, "word/document.xml");
+        assert!(xml.contains("Paid"), "dropdown value not rendered");
+        assert!(xml.contains("Urgent"), "combo box value not rendered");
+        assert!(xml.contains("05.03.2024"), "date not rendered with w:dateFormat");
+        assert!(!xml.contains("due_date"), "placeholder still present");
+    }
+
+    #[test]
+    fn derives_apply_to_dropdown_enums() {
+        use derived::{ContentControls, ContentControlsPriority, ContentControlsStatus};
+        assert!(ContentControlsStatus::Paid.partial_cmp(&ContentControlsStatus::Paid).is_some());
+        let order = |status| {
+            ContentControls::new("Acme", status, ContentControlsPriority::Other("Urgent".into()), docxide_template::Date::new(2024, 3, 5).unwrap())
+        };
+        assert!(order(ContentControlsStatus::Paid).partial_cmp(&order(ContentControlsStatus::Paid)).is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn content_controls_from_json() {
+        let json = r#"{"customer": "Acme", "status": "Paid", "priority": "Urgent", "due_date": "2024-03-05"}"#;
+        let c = ContentControls::from_json(json).unwrap();
+        assert_eq!(c.customer, "Acme");
+        assert_eq!(c.status, ContentControlsStatus::Paid);
+        assert_eq!(c.priority, ContentControlsPriority::Other("Urgent".into()));
+        assert_eq!(c.due_date, docxide_template::Date::new(2024, 3, 5).unwrap());
+        assert!(ContentControls::from_json(r#"{"customer": "Acme"}"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn from_json_accepts_placeholder_spellings() {
+        let doc = HelloWorld::from_json(r#"{"firstName": "Alice", "product_name": "Acme"}"#).unwrap();
+        assert_eq!(doc.first_name, "Alice");
+        assert_eq!(doc.product_name, "Acme");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn from_data_renders_serializable_values() {
+        let data = std::collections::HashMap::from([("firstName", "Alice"), ("product_name", "Acme")]);
+        let bytes = HelloWorld::from_data(&data).unwrap().to_bytes().unwrap();
+        let xml = read_zip_entry(&bytes, "word/document.xml");
+        assert!(xml.contains("Alice") && xml.contains("Acme"));
+        assert!(HelloWorld::from_data(&[("firstName", "Alice")]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn fieldless_template_supports_serde() {
+        fn assert_serde<T: docxide_template::__private::serde::Serialize + docxide_template::__private::serde::de::DeserializeOwned>() {}
+        assert_serde::<EmptyDocument>();
+        assert!(EmptyDocument::from_json("{}").unwrap().to_bytes().is_ok());
+        assert!(EmptyDocument::from_data(&std::collections::HashMap::from([("unused", "value")])).is_ok());
+        assert!(EmptyDocument::from_data(&[1]).is_err());
+        assert!(EmptyDocument::from_json("{").is_err());
+    }
+
+    #[test]
+    fn builder_sets_build_field_with_set_build() {
+        let doc = BuilderNames::builder().set_build("42").default("yes").build().unwrap();
+        assert_eq!(doc.build, "42");
+        assert_eq!(doc.default, "yes");
+    }
+
+    #[test]
+    fn builder_builds_struct() {
+        let doc = HelloWorld::builder().product_name("docxide").first_name("World").build().unwrap();
+        assert_eq!(doc.first_name, "World");
+        assert_eq!(doc.product_name, "docxide");
+    }
+
+    #[test]
+    fn builder_lists_missing_fields() {
+        let err = ContentControls::builder().status(ContentControlsStatus::Paid).build().unwrap_err();
+        assert_eq!(err.fields(), ["customer", "priority", "due_date"]);
+        assert_eq!(err.to_string(), "missing fields: customer, priority, due_date");
+    }
+
+    #[test]
+    fn renamed_placeholders_fill_chosen_fields() {
+        let doc = renamed::UnicodePlaceholders {
+            first_name: "Kari".into(),
+            bedriftsnavn: "Acme".into(),
+            beer_type: "Pils".into(),
+            size: "0,5 l".into(),
+            order_number: "42".into(),
+            case2024_id: "C-1".into(),
+            app_version: "1.0".into(),
+        };
+        let xml = read_zip_entry(&doc.to_bytes().unwrap(), "word/document.xml");
+        assert!(xml.contains("Kjære Kari, velkommen til Acme."), "{}", xml);
+        assert!(xml.contains("Hei Pils, vi har 0,5 l på lager."), "{}", xml);
+        assert!(xml.contains("Dear Kari, your order 42 is ready."), "{}", xml);
+    }
+
+    #[test]
+    fn keyword_placeholders_become_raw_identifiers() {
+        let doc = KeywordPlaceholders::builder()
+            .r#type("invoice")
+            .r#match("exact")
+            .r#async("yes")
+            .self_("me")
+            .build()
+            .unwrap();
+        let xml = read_zip_entry(&doc.to_bytes().unwrap(), "word/document.xml");
+        for text in ["Type: invoice", "Match: exact", "Async: yes", "Self: me"] {
+            assert!(xml.contains(text), "{} missing from {}", text, xml);
+        }
+        let err = KeywordPlaceholders::builder().build().unwrap_err();
+        assert_eq!(err.fields(), ["type", "match", "async", "self_"]);
+    }
+
+    #[test]
+    fn template_renames_apply_to_their_template_only() {
+        let doc = renamed::HelloWorld::new("Alice", "Acme");
+        assert_eq!(doc.recipient, "Alice");
+        let xml = read_zip_entry(&doc.to_bytes().unwrap(), "word/document.xml");
+        assert!(xml.contains("Alice") && !xml.contains("firstName"));
+    }
+
+    #[test]
+    fn keyword_suffix_replaces_raw_identifiers() {
+        let doc = keyword_suffixed::KeywordPlaceholders::new("invoice", "exact", "yes", "me");
+        assert_eq!(
+            [doc.type_value, doc.match_value, doc.async_value, doc.self_value],
+            ["invoice", "exact", "yes", "me"]
+        );
+    }
+
+    #[test]
+    fn colliding_placeholders_merge_by_default() {
+        let doc = CollidingPlaceholders::new("Kari", "Nordmann");
+        let xml = read_zip_entry(&doc.to_bytes().unwrap(), "word/document.xml");
+        for text in ["Dear Kari Nordmann,", "Thank you, Kari.", "Signed for Kari", "Copy to Kari"] {
+            assert!(xml.contains(text), "{} missing from {}", text, xml);
+        }
+    }
+
+    #[test]
+    fn colliding_placeholders_get_suffixed_fields() {
+        let doc = suffixed::CollidingPlaceholders::new("Kari", "Nordmann", "Ola", "Per");
+        let xml = read_zip_entry(&doc.to_bytes().unwrap(), "word/document.xml");
+        for text in ["Dear Kari Nordmann,", "Thank you, Kari.", "Signed for Ola", "Copy to Per"] {
+            assert!(xml.contains(text), "{} missing from {}", text, xml);
+        }
+        assert_eq!((doc.first_name_2.as_str(), doc.first_name_3.as_str()), ("Ola", "Per"));
+    }
+
+    #[test]
+    fn derived_template_matches_generated_struct() {
+        let greeting = hand_written::Greeting { name: "World", product_name: "docxide".into() };
+        assert_eq!(
+            read_zip_entry(&greeting.to_bytes().unwrap(), "word/document.xml"),
+            read_zip_entry(&HelloWorld::new("World", "docxide").to_bytes().unwrap(), "word/document.xml")
+        );
+    }
+
+    #[test]
+    fn derived_template_renders_field_types() {
+        let order = hand_written::Order {
+            customer: "Acme".into(),
+            status: ContentControlsStatus::Paid,
+            priority: 3,
+            due_date: docxide_template::Date::new(2024, 3, 5).unwrap(),
+        };
+        let expected = ContentControls::new(
+            "Acme",
+            ContentControlsStatus::Paid,
+            ContentControlsPriority::Other("3".into()),
+            docxide_template::Date::new(2024, 3, 5).unwrap(),
+        );
+        assert_eq!(
+            read_zip_entry(&order.to_bytes().unwrap(), "word/document.xml"),
+            read_zip_entry(&expected.to_bytes().unwrap(), "word/document.xml")
+        );
+    }
+
+    #[test]
+    fn derived_template_takes_generic_dropdown_types() {
+        let order = hand_written::GenericOrder {
+            customer: "Acme".into(),
+            status: ContentControlsStatus::Paid,
+            priority: 3,
+            due_date: docxide_template::Date::new(2024, 3, 5).unwrap(),
+        };
+        let xml = read_zip_entry(&order.to_bytes().unwrap(), "word/document.xml");
+        assert!(xml.contains("Acme") && xml.contains("Paid"));
+    }
+
+    #[test]
+    fn declared_field_order_wins_over_document_order() {
+        let d = DeclaredOrder::new("Dear Alice,", "Thanks for your order.", "Bob");
+        assert_eq!(d.greeting, "Dear Alice,");
+        assert_eq!(d.body, "Thanks for your order.");
+        assert_eq!(d.signature, "Bob");
+    }
+
+    #[test]
+    fn alphabetical_field_order() {
+        let hf = alphabetical::HeadFootTest::new("bottom", "foo", "header", "top");
+        assert_eq!(
+            [hf.bottom, hf.foo, hf.header, hf.top],
+            ["bottom", "foo", "header", "top"]
+        );
+        // A declared order still wins
+        let d = alphabetical::DeclaredOrder::new("greeting", "body", "signature");
+        assert_eq!(d.greeting, "greeting");
+    }
+
+    // -- Custom delimiters --
+
+    #[test]
+    fn configured_derives_apply_to_generated_structs() {
+        let c = configured::CodeSample::new("Alice", "Bob");
+        assert_eq!(c, c.clone());
+        assert_ne!(c, configured::CodeSample::default());
+        let set: std::collections::HashSet<_> = [c.clone(), c].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn custom_delimiters_ignore_single_braces() {
+        let c = custom_delimiters::CodeSample::new("Alice", "Bob");
+        assert_eq!(c.name, "Alice");
+        assert_eq!(c.sender, "Bob");
+    }
+
+    #[test]
+    fn custom_delimiters_to_bytes_replaces() {
+        let c = custom_delimiters::CodeSample::new("Alice", "Bob");
+        let bytes = c.to_bytes().unwrap();
+        let xml = read_zip_entry(&bytes, "word/document.xml");
+        assert!(xml.contains("Dear Alice,"), "name not replaced");
+        assert!(xml.contains("Bob"), "sender not replaced");
+        assert!(!xml.contains("{{"), "placeholder still present");
+        assert!(xml.contains(r#"{"debug": true, "level": 3}"#), "JSON should be untouched");
+    }
+
+    // -- Escaped braces --
+
+    #[test]
+    fn escaped_braces_are_not_fields() {
+        let e = EscapedBraces::new("Alice", "42");
+        assert_eq!(e.name, "Alice");
+        assert_eq!(e.value, "42");
+    }
+
+    #[test]
+    fn escaped_braces_render_literally() {
+        let bytes = EscapedBraces::new("Alice", "42").to_bytes().unwrap();
+        let xml = read_zip_entry(&bytes, "word/document.xml");
+        assert!(xml.contains("Write {name} to greet Alice."), "escape not rendered: {}", xml);
+        assert!(xml.contains(r#"Config: {"retries": 3}"#), "JSON escape not rendered: {}", xml);
+        assert!(xml.contains("next to 42"), "value not replaced: {}", xml);
+        assert!(!xml.contains('\\'), "backslash left in output: {}", xml);
+    }
+
+    // -- Document properties --
+
+    #[test]
+    fn document_properties_struct_has_fields() {
+        // Field order: body, then core properties, then custom properties
+        let d = DocumentProperties::new("Acme", "Q3 Report", "R-17");
+        assert_eq!(d.client, "Acme");
+        assert_eq!(d.doc_title, "Q3 Report");
+        assert_eq!(d.reference, "R-17");
+    }
+
+    #[test]
+    fn document_properties_to_bytes_replaces() {
+        let bytes = DocumentProperties::new("Acme", "Q3 Report", "R-17").to_bytes().unwrap();
+        let core = read_zip_entry(&bytes, "docProps/core.xml");
+        assert!(core.contains("<dc:title>Q3 Report</dc:title>"), "title not replaced: {}", core);
+        assert!(core.contains("<dc:subject>Report for Acme</dc:subject>"), "subject not replaced: {}", core);
+        let custom = read_zip_entry(&bytes, "docProps/custom.xml");
+        assert!(custom.contains("<vt:lpwstr>R-17</vt:lpwstr>"), "custom property not replaced: {}", custom);
+    }
+
+    #[test]
+    fn render_options_set_document_properties() {
+        let options = docxide_template::RenderOptions::new()
+            .last_modified_by("Billing")
+            .custom_property("Reference", "override")
+            .custom_property("Batch", "7");
+        let bytes = DocumentProperties::new("Acme", "Q3 Report", "R-17")
+            .to_bytes_with(&options)
+            .unwrap();
+        let core = read_zip_entry(&bytes, "docProps/core.xml");
+        assert!(core.contains("<cp:lastModifiedBy>Billing</cp:lastModifiedBy>"), "{}", core);
+        assert!(core.contains("<dc:title>Q3 Report</dc:title>"), "{}", core);
+        let custom = read_zip_entry(&bytes, "docProps/custom.xml");
+        assert!(custom.contains(r#"name="Reference"><vt:lpwstr>override</vt:lpwstr>"#), "{}", custom);
+        assert!(custom.contains(r#"pid="3" name="Batch"><vt:lpwstr>7</vt:lpwstr>"#), "{}", custom);
+    }
+
+    #[test]
+    fn render_options_add_custom_properties_part() {
+        let options = docxide_template::RenderOptions::new()
+            .title("Greeting")
+            .custom_property("Batch", "7");
+        let bytes = HelloWorld::new("World", "docxide").to_bytes_with(&options).unwrap();
+        let core = read_zip_entry(&bytes, "docProps/core.xml");
+        assert!(core.contains("<dc:title>Greeting</dc:title>"), "{}", core);
+        let custom = read_zip_entry(&bytes, "docProps/custom.xml");
+        assert!(custom.contains(r#"name="Batch"><vt:lpwstr>7</vt:lpwstr>"#), "{}", custom);
+        let types = read_zip_entry(&bytes, "[Content_Types].xml");
+        assert!(types.contains(r#"PartName="/docProps/custom.xml""#), "{}", types);
+        let rels = read_zip_entry(&bytes, "_rels/.rels");
+        assert!(rels.contains(r#"Target="docProps/custom.xml""#), "{}", rels);
+    }
+
+    // -- Footnotes, endnotes, comments and text boxes --
+
+    #[test]
+    fn notes_and_text_boxes_struct_has_fields() {
+        // Field order: body and text boxes, footnotes, endnotes, comments
+        let n = NotesAndTextBoxes::new("Topic", "Label", "Archive", "2024-01-01", "Alice");
+        assert_eq!(n.box_label, "Label");
+        assert_eq!(n.subject, "Topic");
+        assert_eq!(n.footnote_source, "Archive");
+        assert_eq!(n.endnote_date, "2024-01-01");
+        assert_eq!(n.reviewer, "Alice");
+    }
+
+    #[test]
+    fn notes_and_text_boxes_to_bytes_replaces() {
+        let n = NotesAndTextBoxes::new("Topic", "Label", "Archive", "2024-01-01", "Alice");
+        let bytes = n.to_bytes().unwrap();
+        let document = read_zip_entry(&bytes, "word/document.xml");
+        assert_eq!(document.matches("Label").count(), 2, "both text box branches should be filled");
+        assert!(!document.contains('{'), "placeholder still present: {}", document);
+        assert!(read_zip_entry(&bytes, "word/footnotes.xml").contains("Source: Archive"));
+        assert!(read_zip_entry(&bytes, "word/endnotes.xml").contains("Printed 2024-01-01"));
+        assert!(read_zip_entry(&bytes, "word/comments.xml").contains("Checked by Alice"));
+    }
+
+    // -- Headers and footers of every section --
+
+    fn multi_section() -> MultiSection {
+        MultiSection::new("Body", "Chapter", "Footer", "Even", "Cover", "Title")
+    }
+
+    #[test]
+    fn multi_section_struct_has_fields() {
+        // Field order: body, the final section's headers and footers, then those
+        // of earlier sections
+        let m = multi_section();
+        assert_eq!(m.body_text, "Body");
+        assert_eq!(m.cover_header, "Cover");
+        assert_eq!(m.title_page_header, "Title");
+        assert_eq!(m.chapter_header, "Chapter");
+        assert_eq!(m.chapter_footer, "Footer");
+        assert_eq!(m.even_footer, "Even");
+    }
+
+    #[test]
+    fn multi_section_to_bytes_replaces() {
+        let bytes = multi_section().to_bytes().unwrap();
+        assert!(read_zip_entry(&bytes, "word/header1.xml").contains("Cover"));
+        assert!(read_zip_entry(&bytes, "word/header2.xml").contains("Title"));
+        assert!(read_zip_entry(&bytes, "word/header3.xml").contains("Chapter: Chapter"));
+        assert!(read_zip_entry(&bytes, "word/footer1.xml").contains("Footer"));
+        assert!(read_zip_entry(&bytes, "word/footer2.xml").contains("Even pages: Even"));
+    }
+
+    fn links_and_fields() -> LinksAndFields {
+        LinksAndFields::new(
+            "Ada \"the Countess\"",
+            "Portrait",
+            "https://docs.example.com/?a=1&b=2",
+            "42",
+            "hello-world",
+        )
+    }
+
+    #[test]
+    fn links_and_fields_struct_has_fields() {
+        // Field order: alt text, field codes, then hyperlink targets
+        let l = links_and_fields();
+        assert_eq!(l.person, "Ada \"the Countess\"");
+        assert_eq!(l.photo_title, "Portrait");
+        assert_eq!(l.docs_url, "https://docs.example.com/?a=1&b=2");
+        assert_eq!(l.user_id, "42");
+        assert_eq!(l.slug, "hello-world");
+    }
+
+    #[test]
+    fn links_and_fields_to_bytes_replaces() {
+        let bytes = links_and_fields().to_bytes().unwrap();
+        let doc = read_zip_entry(&bytes, "word/document.xml");
+        assert!(doc.contains(r#"descr="Photo of Ada &quot;the Countess&quot;""#), "{}", doc);
+        assert!(doc.contains(r#"title="Portrait""#), "{}", doc);
+        assert!(doc.contains(r#" HYPERLINK "https://docs.example.com/?a=1&amp;b=2"#), "{}", doc);
+
+        let rels = read_zip_entry(&bytes, "word/_rels/document.xml.rels");
+        assert!(rels.contains(r#"Target="https://example.com/users/42""#), "{}", rels);
+        assert!(rels.contains(r#"Target="https://example.com/articles/hello-world""#), "{}", rels);
+        assert!(!rels.contains("%7B"), "{}", rels);
+    }
+
+    fn chart_report() -> ChartReport {
+        let point = |month: &str, value: f64| (month.to_string(), value);
+        ChartReport::new(
+            "April",
+            vec![point("Jan", 100.0), point("Feb", 125.5), point("Mar", 150.0), point("Apr", 175.0)],
+            vec![point("Jan", 10.0), point("Feb", 20.0)],
+        )
+    }
+
+    #[test]
+    fn chart_report_struct_has_fields() {
+        let c = chart_report();
+        assert_eq!(c.month, "April");
+        assert_eq!(c.revenue.len(), 4);
+        assert_eq!(c.visitors[1], ("Feb".to_string(), 20.0));
+    }
+
+    #[test]
+    fn chart_report_fills_chart_caches() {
+        let bytes = chart_report().to_bytes().unwrap();
+        assert!(read_zip_entry(&bytes, "word/document.xml").contains("KPI report for April"));
+
+        let chart = read_zip_entry(&bytes, "word/charts/chart1.xml");
+        assert!(chart.contains("<c:v>Revenue</c:v>"), "{}", chart);
+        assert!(!chart.contains("{visitors}"), "{}", chart);
+        assert!(chart.contains("<c:f>Sheet1!$B$2:$B$5</c:f>"), "{}", chart);
+        assert!(chart.contains(r#"<c:pt idx="3"><c:v>Apr</c:v></c:pt>"#), "{}", chart);
+        assert!(chart.contains(r#"<c:pt idx="1"><c:v>125.5</c:v></c:pt>"#), "{}", chart);
+        assert!(chart.contains("<c:f>Sheet1!$C$2:$C$3</c:f>"), "{}", chart);
+        // The unbound series keeps its data
+        assert!(chart.contains("<c:v>Target</c:v>"), "{}", chart);
+        assert!(chart.contains("<c:f>Sheet1!$D$2:$D$4</c:f>"), "{}", chart);
+        // Every series shows the categories of the longest one sharing their range
+        assert_eq!(chart.matches("<c:f>Sheet1!$A$2:$A$5</c:f>").count(), 3, "{}", chart);
+        assert_eq!(chart.matches(r#"<c:pt idx="3"><c:v>Apr</c:v></c:pt>"#).count(), 3, "{}", chart);
+    }
+
+    #[test]
+    fn chart_report_fills_embedded_workbook() {
+        let bytes = chart_report().to_bytes().unwrap();
+        let cursor = Cursor::new(&bytes);
+        let mut archive = zip::ZipArchive::new(cursor).unwrap();
+        let mut workbook = Vec::new();
+        archive
+            .by_name("word/embeddings/Microsoft_Excel_Worksheet.xlsx")
+            .unwrap()
+            .read_to_end(&mut workbook)
+            .unwrap();
+
+        let sheet = read_zip_entry(&workbook, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(r#"<c r="B1" t="inlineStr"><is><t>Revenue</t></is></c>"#), "{}", sheet);
+        // All series share the category range: the shorter one doesn't clear it
+        assert!(sheet.contains(r#"<c r="A4" t="inlineStr"><is><t>Mar</t></is></c>"#), "{}", sheet);
+        assert!(sheet.contains(r#"<c r="A5" t="inlineStr"><is><t>Apr</t></is></c>"#), "{}", sheet);
+        assert!(sheet.contains(r#"<c r="B5"><v>175</v></c>"#), "{}", sheet);
+        assert!(sheet.contains(r#"<c r="C3"><v>20</v></c>"#), "{}", sheet);
+        assert!(!sheet.contains(r#"<c r="C4""#), "{}", sheet);
+        assert!(sheet.contains(r#"<c r="D4"><v>110</v></c>"#), "{}", sheet);
+    }
+
+    #[test]
+    fn chart_report_workbook_is_deterministic() {
+        let options = docxide_template::RenderOptions::new().deterministic(true);
+        let bytes = chart_report().to_bytes_with(&options).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(&bytes)).unwrap();
+        let mut workbook = Vec::new();
+        archive
+            .by_name("word/embeddings/Microsoft_Excel_Worksheet.xlsx")
+            .unwrap()
+            .read_to_end(&mut workbook)
+            .unwrap();
+
+        let template = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/chart_report.docx")).unwrap();
+        let mut template = zip::ZipArchive::new(Cursor::new(template)).unwrap();
+        let mut template_workbook = Vec::new();
+        template
+            .by_name("word/embeddings/Microsoft_Excel_Worksheet.xlsx")
+            .unwrap()
+            .read_to_end(&mut template_workbook)
+            .unwrap();
+        let mut template_workbook = zip::ZipArchive::new(Cursor::new(template_workbook)).unwrap();
+
+        let mut workbook = zip::ZipArchive::new(Cursor::new(workbook)).unwrap();
+        for i in 0..workbook.len() {
+            let entry = workbook.by_index(i).unwrap();
+            assert_eq!(entry.last_modified(), Some(zip::DateTime::default()), "{}", entry.name());
+            let original = template_workbook.by_name(entry.name()).unwrap();
+            assert_eq!(entry.compression(), original.compression(), "{}", entry.name());
+        }
+    }
+
+    #[test]
+    fn prepared_template_renders_repeatedly() {
+        let prepared = docxide_template::PreparedTemplate::from_path(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/HelloWorld.docx")).unwrap();
+        let options = docxide_template::RenderOptions::default();
+        for (first, second) in [("World", "docxide"), ("Alice", "Acme")] {
+            let doc = HelloWorld::new(first, second);
+            let bytes = prepared.render(&doc, &options).unwrap();
+            assert_eq!(
+                read_zip_entry(&bytes, "word/document.xml"),
+                read_zip_entry(&doc.to_bytes().unwrap(), "word/document.xml")
+            );
+        }
+    }
+
+    #[test]
+    fn dynamic_template_matches_generated_struct() {
+        let template = docxide_template::DynamicTemplate::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/templates/HeadFootTest.docx"
+        ))
+        .unwrap();
+        let values: std::collections::HashMap<String, String> = template
+            .placeholders()
+            .iter()
+            .map(|name| (name.to_string(), format!("<{}>", name)))
+            .collect();
+        let bytes = template.render(&values, &docxide_template::RenderOptions::default()).unwrap();
+
+        let hf = HeadFootTest::new("<header>", "<foo>", "<top>", "<bottom>");
+        let expected = hf.to_bytes().unwrap();
+        for part in ["word/document.xml", "word/header1.xml", "word/footer1.xml"] {
+            assert_eq!(read_zip_entry(&bytes, part), read_zip_entry(&expected, part), "{}", part);
+        }
+    }
+
+    #[test]
+    fn write_to_matches_to_bytes() {
+        let doc = HelloWorld::new("World", "docxide");
+        let options = docxide_template::RenderOptions::new().deterministic(true);
+        let mut output = std::io::Cursor::new(Vec::new());
+        doc.write_to_with(&mut output, &options).unwrap();
+        assert_eq!(output.into_inner(), doc.to_bytes_with(&options).unwrap());
+    }
+}